@@ -0,0 +1,681 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ratatui::style::{Color, Modifier};
+
+use crate::cube::LastLayerCase;
+use crate::scramble::{parse_scramble, Puzzle, RngKind, Scramble, MAX_SCRAMBLE_MOVES};
+
+const CONFIG_FILE: &str = "config";
+/// Smaller histogram buckets would mostly hold a single solve each.
+pub const MIN_HISTOGRAM_BUCKET: Duration = Duration::from_millis(10);
+
+/// Options that can be passed as `--<key> <value>` flags. Boolean options can be passed as
+/// `--<key>`. Every flag can also be set in the config file using `<key> = <value>` lines.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub notation: Notation,
+    /// Size of the time ranges in the stats histogram.
+    pub histogram_bucket: Duration,
+    /// Weight of the newest solve in the moving average shown as the current form, in `0..=1`.
+    pub form_alpha: f32,
+    /// Number of solves per set, after which a summary is shown.
+    pub target: Option<usize>,
+    /// Return to idle after inspecting, without solving.
+    pub inspect_only: bool,
+    /// Solves slower than this are marked on the done screen.
+    pub pace_target: Option<Duration>,
+    /// Inspection colors, sorted by descending remaining time.
+    pub warn_levels: Vec<WarnLevel>,
+    /// Serve scrambles from this file instead of random ones.
+    pub scramble_file: Option<PathBuf>,
+    /// Practice executing this algorithm instead of solving scrambles.
+    pub alg: Option<Scramble>,
+    /// Practice this last layer case with scrambles that end in it.
+    pub case: Option<LastLayerCase>,
+    /// Only show the scramble and timer, without any stats.
+    pub minimal: bool,
+    /// Signal the end of a solve.
+    pub feedback: Feedback,
+    /// Show how many solves are missing for averages, instead of hiding them.
+    pub show_solves_needed: bool,
+    /// Show the time the next solve has to beat for a new best ao5 of the session.
+    pub pb_target: bool,
+    pub puzzle: Puzzle,
+    /// Overrides the default length of the puzzle.
+    pub scramble_length: Option<usize>,
+    /// Exclude the first solve of each session from stats.
+    pub skip_warmup: bool,
+    /// Start and stop the timer with a left click. Interferes with selecting text in the
+    /// terminal.
+    pub mouse: bool,
+    /// Shown on the idle screen instead of how to start the timer.
+    pub idle_prompt: Option<String>,
+    /// Ask whether the cube was solved before recording a solve, `n` makes it a DNF.
+    pub confirm_solved: bool,
+    /// Pause and resume solves with `p`, the solves are marked as paused.
+    pub allow_pause: bool,
+    /// Keep showing the inspection time next to the solve time while solving.
+    pub combined: bool,
+    /// Show the stats in a column right of the timer on terminals at least this wide.
+    pub wide_layout: Option<u16>,
+    /// Draw below the prompt instead of on the alternate screen, so the last frame stays in the
+    /// scrollback.
+    pub no_alt_screen: bool,
+    /// How often to retry saving the history after it failed.
+    pub autosave_interval: Duration,
+    /// Show the elapsed inspection time instead of the remaining time.
+    pub inspection_countup: bool,
+    /// Append state transitions to this file, to diagnose timing issues.
+    pub log: Option<PathBuf>,
+    /// Scramble these puzzles and time them as one solve.
+    pub relay: Vec<Puzzle>,
+    /// Colors of the timer, cycled with `p`.
+    pub palette: Palette,
+    /// Generate the same sequence of random scrambles every time.
+    pub seed: Option<u64>,
+    /// The generator of random scrambles.
+    pub rng: RngKind,
+    /// Cycle the scramble colors after being idle this long, `0` disables it.
+    pub screensaver: Option<Duration>,
+    /// Solves faster than this are discarded.
+    pub min_solve_time: Duration,
+    /// Show a bar filling up during inspection, below the time.
+    pub inspection_gauge: bool,
+    /// What space does on the done screen, `n` always continues.
+    pub done_space_action: DoneSpaceAction,
+    /// Number of solves to do each day, progress is shown on the idle screen.
+    pub daily_goal: Option<usize>,
+    /// Blindfolded solving: inspection is memorization without a time limit, ended with space
+    /// and counted towards the solve.
+    pub blind: bool,
+    /// Moves per scramble line, like on printed scramble cards.
+    pub scramble_columns: Option<usize>,
+    /// Keep inspecting past the time limit instead of starting the solve, with a penalty.
+    pub inspection_overtime: bool,
+    /// Show the +2 and DNF warnings of `inspection_overtime` this long before they apply.
+    pub penalty_warn_lead: Duration,
+    /// Show how much of the inspection time was used on the done screen and in the list.
+    pub show_inspection: bool,
+    /// Keep showing the scramble while solving, for practicing notation.
+    pub show_scramble_while_solving: bool,
+    /// Show the mean of 3, for events that use it instead of ao5.
+    pub mean3: bool,
+    /// Also show the session mean without the slowest single.
+    pub mean_without_worst: bool,
+    /// Ignore space and `n` for this long after a solve is done.
+    pub done_grace: Duration,
+    /// Generate this many scrambles, print how long it took and exit. Not documented, it's only
+    /// for checking the performance of the generator.
+    pub bench_gen: Option<usize>,
+    /// Print a line of stats over the whole history and exit.
+    pub oneshot_stats: bool,
+    /// Play this sound file when a solve is done.
+    pub sound: Option<PathBuf>,
+    /// Write the history in `export_format` to this file and exit.
+    pub export: Option<PathBuf>,
+    pub export_format: ExportFormat,
+    /// Keep the history in this SQLite database instead of the JSON file, needs the `sqlite`
+    /// feature.
+    pub db: Option<PathBuf>,
+    /// Return from the done screen to idle after this long.
+    pub auto_next: Option<Duration>,
+    /// A label stored with every solve of the session.
+    pub session_name: Option<String>,
+    /// Track consecutive solves at most this long.
+    pub streak: Option<Duration>,
+    /// Count the best streak over the whole history instead of only this session.
+    pub persist_streak: bool,
+    /// Show a random cube rotation to do before the scramble, which changes the front face.
+    pub scramble_rotation: bool,
+    /// Draw the net and charts with ASCII characters only, for terminals with poor unicode or
+    /// background color support.
+    pub ascii: bool,
+    /// Show the scrambled cube unfolded below the scramble.
+    pub net: bool,
+    /// Show the net in a random orientation, like picking up the cube.
+    pub random_orientation: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// The history with a description of the settings.
+    #[default]
+    Json,
+    /// A file csTimer and Twisty Timer can import.
+    CsTimer,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DoneSpaceAction {
+    /// Continue with the next scramble.
+    #[default]
+    Next,
+    /// Nothing, so the result isn't skipped by accident.
+    Ignore,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Feedback {
+    #[default]
+    None,
+    /// Ring the terminal bell.
+    Bell,
+    /// Briefly invert the screen colors.
+    Flash,
+    Both,
+}
+
+impl Feedback {
+    pub fn bell(&self) -> bool {
+        matches!(self, Self::Bell | Self::Both)
+    }
+
+    pub fn flash(&self) -> bool {
+        matches!(self, Self::Flash | Self::Both)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Palette {
+    #[default]
+    Default,
+    HighContrast,
+    Subtle,
+}
+
+impl Palette {
+    const ALL: [Self; 3] = [Self::Default, Self::HighContrast, Self::Subtle];
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&p| p == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::HighContrast => "high-contrast",
+            Self::Subtle => "subtle",
+        }
+    }
+}
+
+/// Colors shown once the remaining inspection time drops to `remaining`. Configured as a comma
+/// separated list of `<secs>:<fg>:<bg>`, e.g. `8:yellow:#707020, 3:#d09060:#905030`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WarnLevel {
+    pub remaining: Duration,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl WarnLevel {
+    fn parse_list(key: &str, value: &str) -> Result<Vec<Self>, String> {
+        let mut levels = value
+            .split(',')
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|level| {
+                let err = || format!("expected `<secs>:<fg>:<bg>` for `{key}`, found `{level}`");
+                let mut parts = level.split(':').map(str::trim);
+                let (Some(secs), Some(fg), Some(bg), None) =
+                    (parts.next(), parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(err());
+                };
+                Ok(Self {
+                    remaining: parse_secs(key, secs)?,
+                    fg: fg.parse().map_err(|_| err())?,
+                    bg: bg.parse().map_err(|_| err())?,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        levels.sort_by_key(|l| std::cmp::Reverse(l.remaining));
+        Ok(levels)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            notation: Notation::default(),
+            histogram_bucket: Duration::from_secs(1),
+            form_alpha: 0.2,
+            target: None,
+            inspect_only: false,
+            pace_target: None,
+            warn_levels: vec![WarnLevel {
+                remaining: Duration::from_secs(3),
+                fg: Color::Rgb(0xd0, 0x90, 0x60),
+                bg: Color::Rgb(0x90, 0x50, 0x30),
+            }],
+            scramble_file: None,
+            alg: None,
+            case: None,
+            minimal: false,
+            feedback: Feedback::None,
+            show_solves_needed: false,
+            pb_target: false,
+            puzzle: Puzzle::default(),
+            scramble_length: None,
+            skip_warmup: false,
+            mouse: false,
+            idle_prompt: None,
+            confirm_solved: false,
+            allow_pause: false,
+            combined: false,
+            wide_layout: None,
+            no_alt_screen: false,
+            autosave_interval: Duration::from_secs(30),
+            inspection_countup: false,
+            log: None,
+            relay: Vec::new(),
+            palette: Palette::Default,
+            seed: None,
+            rng: RngKind::default(),
+            screensaver: Some(Duration::from_secs(60)),
+            min_solve_time: Duration::from_millis(200),
+            inspection_gauge: false,
+            done_space_action: DoneSpaceAction::Next,
+            daily_goal: None,
+            blind: false,
+            scramble_columns: None,
+            inspection_overtime: false,
+            penalty_warn_lead: Duration::ZERO,
+            show_inspection: false,
+            show_scramble_while_solving: false,
+            mean3: false,
+            mean_without_worst: false,
+            done_grace: Duration::from_millis(250),
+            bench_gen: None,
+            oneshot_stats: false,
+            sound: None,
+            export: None,
+            export_format: ExportFormat::Json,
+            db: None,
+            auto_next: None,
+            session_name: None,
+            streak: None,
+            persist_streak: false,
+            scramble_rotation: false,
+            ascii: false,
+            net: false,
+            random_orientation: false,
+        }
+    }
+}
+
+/// How scramble moves are written out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Notation {
+    /// Inserted between moves.
+    pub separator: String,
+    /// Don't pad moves without a modifier to the width of the others.
+    pub compact: bool,
+    /// Added to the style of the `2` of double turns.
+    pub double_style: Modifier,
+    /// Added to the style of the `'` of reverse turns.
+    pub reverse_style: Modifier,
+}
+
+impl Default for Notation {
+    fn default() -> Self {
+        Self {
+            separator: " ".into(),
+            compact: false,
+            double_style: Modifier::empty(),
+            reverse_style: Modifier::empty(),
+        }
+    }
+}
+
+const BOOL_FLAGS: &[&str] = &[
+    "allow-pause",
+    "ascii",
+    "blind",
+    "combined",
+    "compact",
+    "confirm-solved",
+    "inspect-only",
+    "inspection-countup",
+    "inspection-gauge",
+    "inspection-overtime",
+    "mean-without-worst",
+    "mean3",
+    "minimal",
+    "mouse",
+    "net",
+    "no-alt-screen",
+    "oneshot-stats",
+    "pb-target",
+    "persist-streak",
+    "random-orientation",
+    "scramble-rotation",
+    "show-inspection",
+    "show-scramble-while-solving",
+    "show-solves-needed",
+    "skip-warmup",
+];
+
+fn config_path() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config").map(|d| d.join(CONFIG_FILE))
+}
+
+/// Sets `key` in the config file, replacing an existing line for it or appending one. Other
+/// lines and comments are kept as is.
+pub fn store(key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+    let Some(path) = config_path() else {
+        return Ok(());
+    };
+    let text = match std::fs::read_to_string(&path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("error reading {}: {e}", path.display()).into()),
+    };
+
+    let entry = format!("{key} = {value}");
+    let mut lines: Vec<_> = text.lines().map(str::to_string).collect();
+    let existing = lines.iter_mut().find(|l| {
+        l.split_once('=').is_some_and(|(k, _)| {
+            !l.trim_start().starts_with('#') && k.trim().replace('_', "-") == key
+        })
+    });
+    match existing {
+        Some(line) => *line = entry,
+        None => lines.push(entry),
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+impl Config {
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let mut config = Self::default();
+        if let Some(path) = config_path() {
+            config.read_file(&path)?;
+        }
+        config.parse_args(std::env::args().skip(1))?;
+        Ok(config)
+    }
+
+    fn read_file(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(format!("error reading {}: {e}", path.display()).into()),
+        };
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(
+                    format!("{}:{}: expected `<key> = <value>`", path.display(), i + 1).into(),
+                );
+            };
+            let value = value.trim();
+            let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                Some(unquoted) => unquoted,
+                None => value,
+            };
+            self.set(key.trim(), value)
+                .map_err(|e| format!("{}:{}: {e}", path.display(), i + 1))?;
+        }
+
+        Ok(())
+    }
+
+    fn parse_args(&mut self, args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            let Some(key) = arg.strip_prefix("--") else {
+                return Err(format!("unexpected argument `{arg}`").into());
+            };
+            if BOOL_FLAGS.contains(&key) {
+                self.set(key, "true")?;
+                continue;
+            }
+            let Some(value) = args.next() else {
+                return Err(format!("missing value for `--{key}`").into());
+            };
+            self.set(key, &value)?;
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key.replace('_', "-").as_str() {
+            "separator" => self.notation.separator = value.into(),
+            "compact" => self.notation.compact = parse_bool(key, value)?,
+            "double-style" => self.notation.double_style = parse_modifier(key, value)?,
+            "reverse-style" => self.notation.reverse_style = parse_modifier(key, value)?,
+            "histogram-bucket" => match parse_secs(key, value)? {
+                bucket if bucket < MIN_HISTOGRAM_BUCKET => {
+                    return Err(format!("`{key}` must be at least 0.01s"));
+                }
+                bucket => self.histogram_bucket = bucket,
+            },
+            "form-alpha" => match value.parse::<f32>() {
+                Ok(alpha) if alpha > 0.0 && alpha <= 1.0 => self.form_alpha = alpha,
+                _ => {
+                    return Err(format!(
+                        "expected a number in (0, 1] for `{key}`, found `{value}`"
+                    ))
+                }
+            },
+            "target" => self.target = Some(parse_count(key, value)?),
+            "inspect-only" => self.inspect_only = parse_bool(key, value)?,
+            "pace-target" => self.pace_target = Some(parse_secs(key, value)?),
+            "warn-levels" => self.warn_levels = WarnLevel::parse_list(key, value)?,
+            "scramble-file" => self.scramble_file = Some(value.into()),
+            "alg" => {
+                let alg = parse_scramble(value).map_err(|e| format!("invalid `{key}`: {e}"))?;
+                self.alg = Some(alg);
+            }
+            "case" => self.case = Some(value.parse()?),
+            "minimal" => self.minimal = parse_bool(key, value)?,
+            "show-solves-needed" => self.show_solves_needed = parse_bool(key, value)?,
+            "pb-target" => self.pb_target = parse_bool(key, value)?,
+            // retried this often after a failed save, shorter would keep rewriting the history
+            "autosave-interval" => match parse_secs(key, value)? {
+                interval if interval < Duration::from_secs(1) => {
+                    return Err(format!("`{key}` must be at least 1s"));
+                }
+                interval => self.autosave_interval = interval,
+            },
+            "inspection-countup" => self.inspection_countup = parse_bool(key, value)?,
+            "relay" => {
+                self.relay = value
+                    .split(',')
+                    .map(|p| p.trim().parse())
+                    .collect::<Result<_, _>>()?;
+            }
+            "palette" => {
+                self.palette = match Palette::ALL.iter().find(|p| p.name() == value) {
+                    Some(&p) => p,
+                    None => {
+                        let expected = "`default`, `high-contrast` or `subtle`";
+                        return Err(format!("expected {expected} for `{key}`, found `{value}`"));
+                    }
+                }
+            }
+            "seed" => match value.parse() {
+                Ok(seed) => self.seed = Some(seed),
+                Err(_) => return Err(format!("expected a number for `{key}`, found `{value}`")),
+            },
+            "screensaver" => {
+                let after = parse_secs(key, value)?;
+                self.screensaver = (!after.is_zero()).then_some(after);
+            }
+            "auto-next" => {
+                let after = parse_secs(key, value)?;
+                self.auto_next = (!after.is_zero()).then_some(after);
+            }
+            "show-scramble-while-solving" => {
+                self.show_scramble_while_solving = parse_bool(key, value)?;
+            }
+            "inspection-overtime" => self.inspection_overtime = parse_bool(key, value)?,
+            "penalty-warn-lead" => self.penalty_warn_lead = parse_secs(key, value)?,
+            "show-inspection" => self.show_inspection = parse_bool(key, value)?,
+            "mean3" => self.mean3 = parse_bool(key, value)?,
+            "mean-without-worst" => self.mean_without_worst = parse_bool(key, value)?,
+            "done-grace" => self.done_grace = parse_secs(key, value)?,
+            "min-solve-time" => self.min_solve_time = parse_secs(key, value)?,
+            "inspection-gauge" => self.inspection_gauge = parse_bool(key, value)?,
+            "done-space-action" => {
+                self.done_space_action = match value {
+                    "next" => DoneSpaceAction::Next,
+                    "ignore" => DoneSpaceAction::Ignore,
+                    _ => {
+                        let expected = "`next` or `ignore`";
+                        return Err(format!("expected {expected} for `{key}`, found `{value}`"));
+                    }
+                }
+            }
+            "daily-goal" => self.daily_goal = Some(parse_count(key, value)?),
+            "blind" => self.blind = parse_bool(key, value)?,
+            "scramble-columns" => self.scramble_columns = Some(parse_count(key, value)?),
+            "streak" => self.streak = Some(parse_secs(key, value)?),
+            "persist-streak" => self.persist_streak = parse_bool(key, value)?,
+            "scramble-rotation" => self.scramble_rotation = parse_bool(key, value)?,
+            "ascii" => self.ascii = parse_bool(key, value)?,
+            "net" => self.net = parse_bool(key, value)?,
+            "random-orientation" => self.random_orientation = parse_bool(key, value)?,
+            "log" => self.log = Some(value.into()),
+            "export" => self.export = Some(value.into()),
+            "db" if cfg!(feature = "sqlite") => self.db = Some(value.into()),
+            "db" => return Err(format!("`{key}` needs a build with the `sqlite` feature")),
+            "export-format" => {
+                self.export_format = match value {
+                    "json" => ExportFormat::Json,
+                    "cstimer" => ExportFormat::CsTimer,
+                    _ => {
+                        let expected = "`json` or `cstimer`";
+                        return Err(format!("expected {expected} for `{key}`, found `{value}`"));
+                    }
+                }
+            }
+            "sound" if cfg!(feature = "sound") => self.sound = Some(value.into()),
+            "sound" => return Err(format!("`{key}` needs a build with the `sound` feature")),
+            "bench-gen" => self.bench_gen = Some(parse_count(key, value)?),
+            "oneshot-stats" => self.oneshot_stats = parse_bool(key, value)?,
+            "session-name" => self.session_name = Some(value.into()),
+            "mouse" => self.mouse = parse_bool(key, value)?,
+            "idle-prompt" => self.idle_prompt = Some(value.into()),
+            "confirm-solved" => self.confirm_solved = parse_bool(key, value)?,
+            "allow-pause" => self.allow_pause = parse_bool(key, value)?,
+            "combined" => self.combined = parse_bool(key, value)?,
+            "no-alt-screen" => self.no_alt_screen = parse_bool(key, value)?,
+            "wide-layout" => {
+                self.wide_layout = Some(parse_count(key, value)?.min(u16::MAX as usize) as u16);
+            }
+            "skip-warmup" => self.skip_warmup = parse_bool(key, value)?,
+            "puzzle" => self.puzzle = value.parse()?,
+            "rng" => self.rng = value.parse()?,
+            "scramble-length" => match parse_count(key, value)? {
+                n if n > MAX_SCRAMBLE_MOVES => {
+                    return Err(format!("`{key}` can be at most {MAX_SCRAMBLE_MOVES}"));
+                }
+                n => self.scramble_length = Some(n),
+            },
+            "feedback" => {
+                self.feedback = match value {
+                    "none" => Feedback::None,
+                    "bell" => Feedback::Bell,
+                    "flash" => Feedback::Flash,
+                    "both" => Feedback::Both,
+                    _ => {
+                        let expected = "`none`, `bell`, `flash` or `both`";
+                        return Err(format!("expected {expected} for `{key}`, found `{value}`"));
+                    }
+                }
+            }
+            _ => return Err(format!("unknown option `{key}`")),
+        }
+        Ok(())
+    }
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!(
+            "expected `true` or `false` for `{key}`, found `{value}`"
+        )),
+    }
+}
+
+fn parse_modifier(key: &str, value: &str) -> Result<Modifier, String> {
+    match value {
+        "none" => Ok(Modifier::empty()),
+        "bold" => Ok(Modifier::BOLD),
+        "dim" => Ok(Modifier::DIM),
+        "italic" => Ok(Modifier::ITALIC),
+        "underlined" => Ok(Modifier::UNDERLINED),
+        _ => {
+            let expected = "`none`, `bold`, `dim`, `italic` or `underlined`";
+            Err(format!("expected {expected} for `{key}`, found `{value}`"))
+        }
+    }
+}
+
+fn parse_count(key: &str, value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!(
+            "expected a positive number for `{key}`, found `{value}`"
+        )),
+    }
+}
+
+fn parse_secs(key: &str, value: &str) -> Result<Duration, String> {
+    // also rejects negative and too large values, which `Duration::from_secs_f64` panics on
+    value
+        .parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| format!("expected seconds for `{key}`, found `{value}`"))
+}
+
+/// The directory in the environment variable `var`, falling back to `~/<fallback>`, joined
+/// with the app name.
+pub fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    let base = match std::env::var_os(var) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(fallback),
+    };
+    Some(base.join("cube-tuimer"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secs_out_of_range() {
+        assert_eq!(
+            parse_secs("done-grace", "0.25"),
+            Ok(Duration::from_millis(250))
+        );
+        for value in ["1e20", "-1", "inf", "NaN", "soon"] {
+            assert_eq!(
+                parse_secs("pace-target", value),
+                Err(format!(
+                    "expected seconds for `pace-target`, found `{value}`"
+                ))
+            );
+        }
+        let mut config = Config::default();
+        assert!(config.set("pace-target", "1e20").is_err());
+        assert_eq!(config.pace_target, None);
+    }
+}
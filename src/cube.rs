@@ -0,0 +1,484 @@
+use rand::Rng;
+
+use crate::scramble::{parse_scramble, Dir, Move, Scramble, ScrambleMove};
+
+type Vec3 = [i8; 3];
+
+const FACE_F: usize = 0;
+const FACE_R: usize = 3;
+const FACE_U: usize = 4;
+const FACE_D: usize = 5;
+/// The side faces in the order they pass by the front on `U`.
+const SIDES: [usize; 4] = [0, 3, 1, 2];
+
+/// Combinations of algorithms [`case_scramble`] tries before falling back to the plain setup.
+const CASE_TRIES: usize = 1000;
+
+/// Face order of [`CubeState::faces`], the same as [`ScrambleMove::face_index`].
+const NORMALS: [Vec3; 6] = [
+    [0, 0, 1],  // F
+    [0, 0, -1], // B
+    [-1, 0, 0], // L
+    [1, 0, 0],  // R
+    [0, 1, 0],  // U
+    [0, -1, 0], // D
+];
+
+/// The directions of the columns and rows of each face, as laid out in the net, with `U` above
+/// and `D` below `F`.
+const FACE_AXES: [(Vec3, Vec3); 6] = [
+    ([1, 0, 0], [0, -1, 0]),  // F
+    ([-1, 0, 0], [0, -1, 0]), // B
+    ([0, 0, 1], [0, -1, 0]),  // L
+    ([0, 0, -1], [0, -1, 0]), // R
+    ([1, 0, 0], [0, 0, 1]),   // U
+    ([1, 0, 0], [0, 0, -1]),  // D
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Sticker {
+    /// Position of the piece, each coordinate in `-1..=1`. `x` points right, `y` up and `z` to
+    /// the front.
+    pos: Vec3,
+    /// The direction the sticker faces.
+    normal: Vec3,
+    /// The face the sticker belongs to when solved.
+    color: u8,
+}
+
+/// The sticker colors of a 3x3. Only tracks facelets, without any notion of pieces, which is
+/// enough to show a scramble. A 2x2 is the corners of a 3x3.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CubeState {
+    stickers: [Sticker; 54],
+}
+
+/// A rotation of the whole cube, following the face of the same axis: `x` like `R`, `y` like
+/// `U` and `z` like `F`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    X,
+    Y,
+    Z,
+}
+
+/// Formatted like a move, e.g. `x'` or `y2`.
+impl std::fmt::Display for Rotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::X => "x",
+            Self::Y => "y",
+            Self::Z => "z",
+        })
+    }
+}
+
+/// Rotations that bring the cube into one of its 24 orientations, as `(rotation, turns)` pairs
+/// with `turns` in `1..=3`. No rotations for the default orientation.
+pub fn random_rotations(rng: &mut impl Rng) -> Vec<(Rotation, u8)> {
+    // bring any of the 6 faces on top, then any of the 4 to the front
+    let mut rotations = match rng.gen_range(0..6) {
+        0 => vec![],
+        1 => vec![(Rotation::X, 1)],
+        2 => vec![(Rotation::X, 2)],
+        3 => vec![(Rotation::X, 3)],
+        4 => vec![(Rotation::Z, 1)],
+        _ => vec![(Rotation::Z, 3)],
+    };
+    match rng.gen_range(0..4) {
+        0 => (),
+        turns => rotations.push((Rotation::Y, turns)),
+    }
+    rotations
+}
+
+/// The rotations separated by spaces, e.g. `x2 y'`.
+pub fn format_rotations(rotations: &[(Rotation, u8)]) -> String {
+    let rotations: Vec<_> = rotations
+        .iter()
+        .map(|(rotation, turns)| match turns {
+            2 => format!("{rotation}2"),
+            3 => format!("{rotation}'"),
+            _ => rotation.to_string(),
+        })
+        .collect();
+    rotations.join(" ")
+}
+
+/// Well-known last layer cases to practice with `--case`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LastLayerCase {
+    Sune,
+    AntiSune,
+    DoubleSune,
+    TPerm,
+    UaPerm,
+    UbPerm,
+    HPerm,
+}
+
+impl std::fmt::Display for LastLayerCase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let i = Self::ALL.iter().position(|c| c == self).unwrap_or(0);
+        f.write_str(Self::NAMES[i])
+    }
+}
+
+impl std::str::FromStr for LastLayerCase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::NAMES.iter().position(|&n| n == s) {
+            Some(i) => Ok(Self::ALL[i]),
+            None => Err(format!(
+                "unknown case `{s}`, expected one of {}",
+                Self::NAMES.map(|n| format!("`{n}`")).join(", ")
+            )),
+        }
+    }
+}
+
+impl LastLayerCase {
+    pub const ALL: [Self; 7] = [
+        Self::Sune,
+        Self::AntiSune,
+        Self::DoubleSune,
+        Self::TPerm,
+        Self::UaPerm,
+        Self::UbPerm,
+        Self::HPerm,
+    ];
+    const NAMES: [&'static str; 7] = [
+        "sune",
+        "anti-sune",
+        "double-sune",
+        "t-perm",
+        "ua-perm",
+        "ub-perm",
+        "h-perm",
+    ];
+
+    /// A common algorithm solving the case.
+    pub fn alg(self) -> Scramble {
+        let alg = match self {
+            Self::Sune => "R U R' U R U2 R'",
+            Self::AntiSune => "R U2 R' U' R U' R'",
+            Self::DoubleSune => "R U2 R' U' R U R' U' R U' R'",
+            Self::TPerm => "R U R' U' R' F R2 U' R' U' R U R' F'",
+            Self::UaPerm => "R U' R U R U R U' R' U' R2",
+            Self::UbPerm => "R2 U R U R' U' R' U' R' U R'",
+            Self::HPerm => "R2 U2 R U2 R2 U2 R2 U2 R U2 R2",
+        };
+        parse_scramble(alg).expect("valid algorithm")
+    }
+
+    /// Whether the case only permutes an already oriented last layer.
+    fn is_pll(self) -> bool {
+        matches!(
+            self,
+            Self::TPerm | Self::UaPerm | Self::UbPerm | Self::HPerm
+        )
+    }
+}
+
+/// Whether the first two layers are solved and the last layer is in `case`, in any `U` turn
+/// before or after it. Orientation cases match whatever the permutation of the last layer.
+pub fn matches_case(state: &CubeState, case: LastLayerCase) -> bool {
+    if !f2l_solved(&state.faces()) {
+        return false;
+    }
+    // the case seen from each side, which is a `U` turn before the algorithm
+    let setup = case.alg().inverse();
+    let references: Vec<_> = (0..4)
+        .map(|turns| {
+            let mut state = CubeState::solved();
+            if let Some(auf) = Move::turns(Dir::Up, turns) {
+                state.apply(auf);
+            }
+            for &mov in setup.moves() {
+                state.apply(mov);
+            }
+            state.faces()
+        })
+        .collect();
+    let pattern = last_layer_pattern(&references[0]);
+
+    // a `U` turn after the algorithm
+    let mut state = *state;
+    for _ in 0..4 {
+        let faces = state.faces();
+        let matches = match case.is_pll() {
+            true => references.contains(&faces),
+            false => last_layer_pattern(&faces) == pattern,
+        };
+        if matches {
+            return true;
+        }
+        state.apply(Move::turns(Dir::Up, 1).expect("quarter turn"));
+    }
+    false
+}
+
+/// A scramble leaving the cube in `case`. Built from random combinations of the known
+/// algorithms with `U` turns in between, so orientation cases come with varying permutations,
+/// keeping only those that end up in `case`.
+pub fn case_scramble(rng: &mut impl Rng, case: LastLayerCase) -> Scramble {
+    let auf = |rng: &mut _, scramble: &mut Scramble| {
+        if let Some(auf) = Move::turns(Dir::Up, Rng::gen_range(rng, 0..4)) {
+            scramble.push(auf);
+        }
+    };
+    for _ in 0..CASE_TRIES {
+        let mut scramble = Scramble::empty();
+        for _ in 0..rng.gen_range(1..=2) {
+            auf(rng, &mut scramble);
+            let setup = LastLayerCase::ALL[rng.gen_range(0..LastLayerCase::ALL.len())];
+            for &mov in setup.alg().inverse().moves() {
+                scramble.push(mov);
+            }
+        }
+        auf(rng, &mut scramble);
+        if matches_case(&CubeState::scrambled(&scramble), case) {
+            return scramble;
+        }
+    }
+    case.alg().inverse()
+}
+
+/// Whether the `D` face and the lower two rows of the sides are solved.
+fn f2l_solved(faces: &[[u8; 9]; 6]) -> bool {
+    let solved = |face: usize, stickers: std::ops::Range<usize>| {
+        faces[face][stickers].iter().all(|&c| c == faces[face][4])
+    };
+    solved(FACE_D, 0..9) && SIDES.iter().all(|&face| solved(face, 3..9))
+}
+
+/// Which stickers of the `U` face and the top rows of the sides show the `U` color.
+fn last_layer_pattern(faces: &[[u8; 9]; 6]) -> [bool; 21] {
+    let up = faces[FACE_U][4];
+    let mut pattern = [false; 21];
+    let stickers = faces[FACE_U]
+        .iter()
+        .chain(SIDES.iter().flat_map(|&face| &faces[face][..3]));
+    for (p, &c) in pattern.iter_mut().zip(stickers) {
+        *p = c == up;
+    }
+    pattern
+}
+
+impl Default for CubeState {
+    fn default() -> Self {
+        Self::solved()
+    }
+}
+
+impl CubeState {
+    pub fn solved() -> Self {
+        let mut stickers = [Sticker {
+            pos: [0; 3],
+            normal: [0; 3],
+            color: 0,
+        }; 54];
+        for face in 0..6 {
+            let normal = NORMALS[face];
+            let (right, down) = FACE_AXES[face];
+            for i in 0..9 {
+                let (col, row) = ((i % 3) as i8 - 1, (i / 3) as i8 - 1);
+                let pos = std::array::from_fn(|a| normal[a] + right[a] * col + down[a] * row);
+                stickers[face * 9 + i] = Sticker {
+                    pos,
+                    normal,
+                    color: face as u8,
+                };
+            }
+        }
+        Self { stickers }
+    }
+
+    pub fn scrambled(scramble: &Scramble) -> Self {
+        let mut state = Self::solved();
+        for &mov in scramble.moves() {
+            state.apply(mov);
+        }
+        state
+    }
+
+    pub fn apply(&mut self, mov: Move) {
+        let axis = NORMALS[mov.face_index() as usize];
+        self.turn(axis, mov.quarter_turns(), |s| dot(s.pos, axis) == 1);
+    }
+
+    /// Rotates the whole cube, which changes how it is shown but not how scrambled it is.
+    pub fn rotate(&mut self, rotation: Rotation, turns: u8) {
+        let axis = match rotation {
+            Rotation::X => NORMALS[FACE_R],
+            Rotation::Y => NORMALS[FACE_U],
+            Rotation::Z => NORMALS[FACE_F],
+        };
+        self.turn(axis, turns, |_| true);
+    }
+
+    /// One of the 24 orientations at random, the same on the same `rng` state.
+    pub fn rotate_randomly(&mut self, rng: &mut impl Rng) {
+        for (rotation, turns) in random_rotations(rng) {
+            self.rotate(rotation, turns);
+        }
+    }
+
+    /// Turns the stickers for which `layer` is true clockwise around `axis`, as seen from the
+    /// face it points to.
+    fn turn(&mut self, axis: Vec3, turns: u8, layer: impl Fn(&Sticker) -> bool) {
+        for sticker in self.stickers.iter_mut().filter(|s| layer(s)) {
+            for _ in 0..turns {
+                sticker.pos = rotate_cw(sticker.pos, axis);
+                sticker.normal = rotate_cw(sticker.normal, axis);
+            }
+        }
+    }
+
+    /// The colors of each face in reading order, as laid out in the net.
+    pub fn faces(&self) -> [[u8; 9]; 6] {
+        let mut faces = [[0; 9]; 6];
+        for s in &self.stickers {
+            let face = NORMALS.iter().position(|&n| n == s.normal).unwrap_or(0);
+            let (right, down) = FACE_AXES[face];
+            let col = dot(s.pos, right) + 1;
+            let row = dot(s.pos, down) + 1;
+            faces[face][(row * 3 + col) as usize] = s.color;
+        }
+        faces
+    }
+}
+
+fn dot(a: Vec3, b: Vec3) -> i8 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Rotates `v` by a quarter turn clockwise around the unit vector `axis`, when looking at the
+/// origin from the tip of `axis`.
+fn rotate_cw(v: Vec3, axis: Vec3) -> Vec3 {
+    let cross = [
+        axis[1] * v[2] - axis[2] * v[1],
+        axis[2] * v[0] - axis[0] * v[2],
+        axis[0] * v[1] - axis[1] * v[0],
+    ];
+    let along = dot(axis, v);
+    std::array::from_fn(|a| axis[a] * along - cross[a])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scramble::parse_scramble;
+
+    fn after(moves: &str) -> CubeState {
+        CubeState::scrambled(&parse_scramble(moves).unwrap())
+    }
+
+    #[test]
+    fn move_orders() {
+        assert_eq!(after("R R R R"), CubeState::solved());
+        assert_eq!(after("F2 F2"), CubeState::solved());
+        assert_eq!(after("U U'"), CubeState::solved());
+        assert_eq!(after(&"R U R' U' ".repeat(6)), CubeState::solved());
+        assert_ne!(after(&"R U R' U' ".repeat(3)), CubeState::solved());
+    }
+
+    #[test]
+    fn r_moves_front_to_up() {
+        let faces = after("R").faces();
+        let [f, _, _, r, u, d] = [0, 1, 2, 3, 4, 5];
+        // the right column of F comes from D, the one of U from F
+        for i in [2, 5, 8] {
+            assert_eq!(faces[f][i], d as u8);
+            assert_eq!(faces[u][i], f as u8);
+        }
+        assert_eq!(faces[r], [r as u8; 9]);
+
+        // the top row of F comes from R
+        let faces = after("U").faces();
+        assert_eq!(faces[f][..3], [r as u8; 3]);
+    }
+
+    #[test]
+    fn rotations_keep_the_cube_solved() {
+        let mut state = after("R U F' D2");
+        let faces = state.faces();
+        state.rotate(Rotation::Y, 4);
+        assert_eq!(state.faces(), faces);
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..24 {
+            let mut solved = CubeState::solved();
+            solved.rotate_randomly(&mut rng);
+            for face in solved.faces() {
+                assert!(face.iter().all(|&c| c == face[4]));
+            }
+        }
+    }
+
+    #[test]
+    fn random_rotations_reach_every_orientation() {
+        let mut rng = rand::thread_rng();
+        let mut orientations = Vec::new();
+        for _ in 0..1000 {
+            let mut solved = CubeState::solved();
+            for (rotation, turns) in random_rotations(&mut rng) {
+                solved.rotate(rotation, turns);
+            }
+            let centers = solved.faces().map(|face| face[4]);
+            if !orientations.contains(&centers) {
+                orientations.push(centers);
+            }
+        }
+        assert_eq!(orientations.len(), 24);
+        assert_eq!(
+            format_rotations(&[(Rotation::X, 2), (Rotation::Y, 3)]),
+            "x2 y'"
+        );
+    }
+
+    #[test]
+    fn cases_match_only_their_setup() {
+        assert!(!matches_case(&CubeState::solved(), LastLayerCase::Sune));
+        assert!(!matches_case(&after("R U R'"), LastLayerCase::Sune));
+
+        let oriented = last_layer_pattern(&CubeState::solved().faces());
+        for case in LastLayerCase::ALL {
+            let state = CubeState::scrambled(&case.alg().inverse());
+            for other in LastLayerCase::ALL {
+                assert_eq!(matches_case(&state, other), case == other, "{case} {other}");
+            }
+            let is_oriented = last_layer_pattern(&state.faces()) == oriented;
+            assert_eq!(is_oriented, case.is_pll(), "{case}");
+            assert_eq!(case.to_string().parse(), Ok(case));
+        }
+
+        // from another side, and with a `U` turn left to do after the algorithm
+        assert!(matches_case(
+            &after("U R U2 R' U' R U' R' U2"),
+            LastLayerCase::Sune
+        ));
+        assert!(matches_case(
+            &after("U' R U R' U R U2 R'"),
+            LastLayerCase::AntiSune
+        ));
+        // the orientation case stays with the last layer permuted
+        let mut state = after("R U2 R' U' R U' R'");
+        for mov in LastLayerCase::TPerm.alg().moves() {
+            state.apply(*mov);
+        }
+        assert!(matches_case(&state, LastLayerCase::Sune));
+    }
+
+    #[test]
+    fn case_scrambles_end_in_the_case() {
+        let mut rng = rand::thread_rng();
+        for case in LastLayerCase::ALL {
+            for _ in 0..10 {
+                let scramble = case_scramble(&mut rng, case);
+                assert!(matches_case(&CubeState::scrambled(&scramble), case));
+            }
+        }
+    }
+}
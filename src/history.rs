@@ -0,0 +1,401 @@
+use std::error::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::{Datelike, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+const HISTORY_FILE: &str = "history.json";
+/// Solves changed since the history was last saved, one JSON object per line.
+const JOURNAL_FILE: &str = "journal.jsonl";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolveResult {
+    pub time: Duration,
+    pub timestamp: SystemTime,
+    #[serde(default)]
+    pub penalty: Penalty,
+    /// A move by move reconstruction or other notes, may span multiple lines.
+    #[serde(default)]
+    pub reconstruction: Option<String>,
+    /// The first solve of a session with `skip-warmup`, not counted in stats.
+    #[serde(default)]
+    pub warmup: bool,
+    /// Memorization time of a blind solve, included in `time`.
+    #[serde(default)]
+    pub memo: Option<Duration>,
+    /// Paused during the solve with `--allow-pause`.
+    #[serde(default)]
+    pub paused: bool,
+    /// How much of the inspection time was used, `None` for blind solves, where it is `memo`.
+    #[serde(default)]
+    pub inspection: Option<Duration>,
+    /// The `--session-name` the solve was done under.
+    #[serde(default)]
+    pub session: Option<String>,
+    /// The scramble the solve was done on, relay scrambles are joined with ` / `.
+    #[serde(default)]
+    pub scramble: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Penalty {
+    #[default]
+    None,
+    Plus2,
+    Dnf,
+}
+
+impl SolveResult {
+    pub fn new(time: Duration) -> Self {
+        Self {
+            time,
+            timestamp: SystemTime::now(),
+            penalty: Penalty::None,
+            reconstruction: None,
+            warmup: false,
+            memo: None,
+            inspection: None,
+            paused: false,
+            session: None,
+            scramble: None,
+        }
+    }
+
+    /// The time including penalties, or `None` for a DNF.
+    pub fn final_time(&self) -> Option<Duration> {
+        match self.penalty {
+            Penalty::None => Some(self.time),
+            Penalty::Plus2 => Some(self.time + Duration::from_secs(2)),
+            Penalty::Dnf => None,
+        }
+    }
+
+    pub fn toggle_penalty(&mut self, penalty: Penalty) {
+        self.penalty = match self.penalty == penalty {
+            true => Penalty::None,
+            false => penalty,
+        };
+    }
+}
+
+/// With a width, e.g. `{solve:8}`, the time is right aligned to it and the penalty suffix is
+/// padded when missing, so solves line up in a column.
+impl std::fmt::Display for SolveResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = f.width().unwrap_or(0);
+        match self.final_time() {
+            Some(time) => f.write_str(&format_time_aligned(time, width))?,
+            None => write!(f, "{:>width$}", "DNF")?,
+        }
+        match self.penalty {
+            Penalty::Plus2 => f.write_str(" (+2)")?,
+            _ if f.width().is_some() => f.write_str("     ")?,
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// Wide enough for times below 1000 seconds.
+pub const TIME_WIDTH: usize = 8;
+
+pub fn format_time(time: Duration) -> String {
+    format!("{:.3}s", time.as_secs_f32())
+}
+
+/// [`format_time`] right aligned to `width` columns.
+pub fn format_time_aligned(time: Duration, width: usize) -> String {
+    format!("{:>width$}", format_time(time))
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    crate::config::xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+pub fn load() -> Result<Vec<SolveResult>, Box<dyn Error>> {
+    let Some(path) = data_dir().map(|d| d.join(HISTORY_FILE)) else {
+        return Ok(Vec::new());
+    };
+    let text = match std::fs::read_to_string(&path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("error reading {}: {e}", path.display()).into()),
+    };
+    let solves = serde_json::from_str(&text)
+        .map_err(|e| format!("error parsing {}: {e}", path.display()))?;
+    Ok(solves)
+}
+
+pub fn save(solves: &[SolveResult]) -> Result<(), Box<dyn Error>> {
+    let Some(dir) = data_dir() else {
+        return Ok(());
+    };
+    std::fs::create_dir_all(&dir)?;
+    let text = serde_json::to_string(solves)?;
+    std::fs::write(dir.join(HISTORY_FILE), text)?;
+    Ok(())
+}
+
+/// Whether the history can be saved, probed by writing a file next to it. Without a data
+/// directory nothing is saved, which is not a failure.
+pub fn writable() -> bool {
+    let Some(dir) = data_dir() else {
+        return true;
+    };
+    let probe = dir.join(".write-test");
+    std::fs::create_dir_all(&dir).is_ok()
+        && std::fs::write(&probe, "").is_ok()
+        && std::fs::remove_file(&probe).is_ok()
+}
+
+/// Appends a new or changed solve to the journal. Much cheaper than [`save`] and only ever adds
+/// to the file, so a solve survives a crash before the next successful save.
+pub fn append_journal(solve: &SolveResult) -> Result<(), Box<dyn Error>> {
+    let Some(dir) = data_dir() else {
+        return Ok(());
+    };
+    std::fs::create_dir_all(&dir)?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(JOURNAL_FILE))?;
+    let line = serde_json::to_string(solve)?;
+    writeln!(file, "{line}")?;
+    file.sync_data()?;
+    Ok(())
+}
+
+/// Empties the journal, once everything in it was saved.
+pub fn clear_journal() -> Result<(), Box<dyn Error>> {
+    let Some(dir) = data_dir() else {
+        return Ok(());
+    };
+    match std::fs::remove_file(dir.join(JOURNAL_FILE)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Applies the journal left by a session that didn't get to save, returning the number of
+/// solves it added or changed.
+pub fn replay_journal(solves: &mut Vec<SolveResult>) -> Result<usize, Box<dyn Error>> {
+    let Some(path) = data_dir().map(|d| d.join(JOURNAL_FILE)) else {
+        return Ok(0);
+    };
+    let text = match std::fs::read_to_string(&path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("error reading {}: {e}", path.display()).into()),
+    };
+    Ok(merge_journal(solves, &text))
+}
+
+/// Every journal entry is newer than the saved history, so it replaces the solve with the same
+/// timestamp or is a solve that was never saved. A line cut off by a crash is skipped.
+fn merge_journal(solves: &mut Vec<SolveResult>, journal: &str) -> usize {
+    let mut changed = 0;
+    for line in journal.lines() {
+        let Ok(solve) = serde_json::from_str::<SolveResult>(line) else {
+            continue;
+        };
+        match solves.iter_mut().find(|s| s.timestamp == solve.timestamp) {
+            Some(saved) if *saved == solve => continue,
+            Some(saved) => *saved = solve,
+            None => solves.push(solve),
+        }
+        changed += 1;
+    }
+    changed
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Summary {
+    pub today: usize,
+    pub week: usize,
+}
+
+impl Summary {
+    /// Counts solves since local midnight and since the start of the week (monday).
+    pub fn new(solves: &[SolveResult]) -> Self {
+        let today = Local::now().date_naive();
+        let week_start = today - chrono::Days::new(today.weekday().num_days_from_monday() as u64);
+        Self {
+            today: count_since(solves, local_midnight(today)),
+            week: count_since(solves, local_midnight(week_start)),
+        }
+    }
+}
+
+/// Number of solves since local midnight.
+pub fn count_today(solves: &[SolveResult]) -> usize {
+    count_since(solves, local_midnight(Local::now().date_naive()))
+}
+
+fn local_midnight(date: chrono::NaiveDate) -> SystemTime {
+    let midnight = date.and_time(chrono::NaiveTime::MIN);
+    match Local.from_local_datetime(&midnight).earliest() {
+        Some(t) => t.into(),
+        None => SystemTime::UNIX_EPOCH,
+    }
+}
+
+fn count_since(solves: &[SolveResult], since: SystemTime) -> usize {
+    solves.iter().filter(|s| s.timestamp >= since).count()
+}
+
+/// Describes how the exported solves were done, so the file can be checked when read again.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ExportMeta {
+    pub version: &'static str,
+    pub puzzle: String,
+    pub scramble_length: usize,
+    pub seed: Option<u64>,
+    pub rng: String,
+}
+
+/// Writes the solves to `path` as a JSON object with the `meta` next to the `solves`.
+pub fn export(
+    path: &Path,
+    meta: &ExportMeta,
+    solves: &[SolveResult],
+) -> Result<(), Box<dyn Error>> {
+    #[derive(Serialize)]
+    struct Export<'a> {
+        meta: &'a ExportMeta,
+        solves: &'a [SolveResult],
+    }
+
+    let text = serde_json::to_string_pretty(&Export { meta, solves })?;
+    std::fs::write(path, text).map_err(|e| format!("error writing {}: {e}", path.display()))?;
+    Ok(())
+}
+
+/// The solves as a csTimer export, which Twisty Timer can import as well. Every
+/// `--session-name` becomes a session, solves without one go to a session named `cube-tuimer`.
+/// Times are in milliseconds with the penalty separate, `2000` for +2 and `-1` for a DNF.
+/// Reconstructions become comments, and solves recorded before scrambles were kept get an empty
+/// scramble.
+pub fn export_cstimer(solves: &[SolveResult]) -> String {
+    let mut names: Vec<&str> = Vec::new();
+    let mut sessions: Vec<Vec<serde_json::Value>> = Vec::new();
+    for solve in solves {
+        let name = solve.session.as_deref().unwrap_or("cube-tuimer");
+        let i = match names.iter().position(|&n| n == name) {
+            Some(i) => i,
+            None => {
+                names.push(name);
+                sessions.push(Vec::new());
+                names.len() - 1
+            }
+        };
+        let penalty = match solve.penalty {
+            Penalty::None => 0,
+            Penalty::Plus2 => 2000,
+            Penalty::Dnf => -1,
+        };
+        let timestamp = solve
+            .timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |t| t.as_secs());
+        sessions[i].push(serde_json::json!([
+            [penalty, solve.time.as_millis() as u64],
+            solve.scramble.as_deref().unwrap_or_default(),
+            solve.reconstruction.as_deref().unwrap_or_default(),
+            timestamp,
+        ]));
+    }
+
+    let mut export = serde_json::Map::new();
+    let mut session_data = serde_json::Map::new();
+    for (i, (name, solves)) in names.iter().zip(sessions).enumerate() {
+        export.insert(format!("session{}", i + 1), solves.into());
+        let data = serde_json::json!({ "name": name, "opt": {}, "rank": i + 1 });
+        session_data.insert((i + 1).to_string(), data);
+    }
+    // csTimer keeps the session data as a string of JSON
+    let session_data = serde_json::Value::from(session_data).to_string();
+    export.insert(
+        "properties".into(),
+        serde_json::json!({ "sessionData": session_data }),
+    );
+    serde_json::Value::from(export).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cstimer_sessions_and_penalties() {
+        let mut solves: Vec<_> = [12_345, 9_870, 15_000]
+            .map(|ms| SolveResult::new(Duration::from_millis(ms)))
+            .into();
+        for (i, solve) in solves.iter_mut().enumerate() {
+            solve.timestamp =
+                SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + i as u64);
+        }
+        solves[1].penalty = Penalty::Plus2;
+        solves[1].reconstruction = Some("R U R'".into());
+        solves[2].penalty = Penalty::Dnf;
+        solves[2].session = Some("oh".into());
+        solves[0].scramble = Some("R U2 F' D".into());
+
+        // laid out like a csTimer export: `sessionN` lists of `[[penalty, ms], scramble,
+        // comment, timestamp]` and the session names and order in `properties.sessionData`,
+        // which is itself a string of JSON
+        let export: serde_json::Value = serde_json::from_str(&export_cstimer(&solves)).unwrap();
+        let session_data = export["properties"]["sessionData"].as_str().unwrap();
+        let session_data: serde_json::Value = serde_json::from_str(session_data).unwrap();
+        assert_eq!(session_data["1"]["name"], "cube-tuimer");
+        assert_eq!(session_data["1"]["rank"], 1);
+        assert_eq!(session_data["2"]["name"], "oh");
+        assert_eq!(session_data["2"]["rank"], 2);
+
+        let session1 = export["session1"].as_array().unwrap();
+        let session2 = export["session2"].as_array().unwrap();
+        assert_eq!(session1.len(), 2);
+        assert_eq!(session2.len(), 1);
+        for solve in session1.iter().chain(session2) {
+            let solve = solve.as_array().unwrap();
+            assert_eq!(solve.len(), 4);
+            assert_eq!(solve[0].as_array().unwrap().len(), 2);
+            assert!(solve[1].is_string() && solve[2].is_string() && solve[3].is_u64());
+        }
+        assert_eq!(
+            session1[0],
+            serde_json::json!([[0, 12_345], "R U2 F' D", "", 1_700_000_000u64])
+        );
+        assert_eq!(
+            session1[1],
+            serde_json::json!([[2000, 9_870], "", "R U R'", 1_700_000_001u64])
+        );
+        assert_eq!(
+            session2[0],
+            serde_json::json!([[-1, 15_000], "", "", 1_700_000_002u64])
+        );
+    }
+
+    #[test]
+    fn journal_replaces_and_adds_solves() {
+        let saved = SolveResult::new(Duration::from_secs(10));
+        let mut changed = saved.clone();
+        changed.penalty = Penalty::Plus2;
+        let mut unsaved = SolveResult::new(Duration::from_secs(12));
+        unsaved.timestamp += Duration::from_secs(30);
+
+        let journal = [&saved, &changed, &unsaved]
+            .map(|s| serde_json::to_string(s).unwrap())
+            .join("\n");
+        // cut off in the middle of a line
+        let journal = format!("{journal}\n{{\"time\":{{\"se");
+
+        let mut solves = vec![saved];
+        assert_eq!(merge_journal(&mut solves, &journal), 2);
+        assert_eq!(solves, [changed.clone(), unsaved.clone()]);
+        // replaying again ends up the same
+        merge_journal(&mut solves, &journal);
+        assert_eq!(solves, [changed, unsaved]);
+    }
+}
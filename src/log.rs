@@ -0,0 +1,34 @@
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+static LOG: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Appends all following [`write`]s to the file at `path`.
+pub fn open(path: &Path) -> Result<(), Box<dyn Error>> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("error opening {}: {e}", path.display()))?;
+    _ = LOG.set(Mutex::new(file));
+    Ok(())
+}
+
+pub fn enabled() -> bool {
+    LOG.get().is_some()
+}
+
+/// Writes a line prefixed with the local time, if a log file was opened. Errors are ignored
+/// since logging is only a debugging aid.
+pub fn write(msg: std::fmt::Arguments) {
+    let Some(file) = LOG.get() else {
+        return;
+    };
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+    if let Ok(mut file) = file.lock() {
+        _ = writeln!(file, "{now} {msg}");
+    }
+}
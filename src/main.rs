@@ -1,196 +1,652 @@
+use std::cmp::Ordering;
 use std::error::Error;
-use std::fmt::Write;
+use std::fmt::Write as _;
+use std::io::{IsTerminal as _, Write as _};
+use std::ops::Range;
 use std::time::{Duration, Instant};
 
-use crossterm::event::{Event, KeyCode, KeyEventKind};
+use chrono::Timelike as _;
+use crossterm::event::{
+    DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+    MouseButton, MouseEventKind,
+};
 use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
-use rand::Rng;
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::Alignment;
-use ratatui::style::{Color, Style, Stylize};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
 use ratatui::text::{Line, Span, Text};
-use ratatui::widgets::{Block, Padding, Paragraph};
-use ratatui::Frame;
+use ratatui::widgets::{
+    Bar, BarChart, BarGroup, Block, Borders, Clear, Gauge, Padding, Paragraph, Sparkline, Wrap,
+};
+use ratatui::{symbols, Frame, TerminalOptions, Viewport};
+
+use crate::config::{Config, DoneSpaceAction, ExportFormat, Notation, Palette, WarnLevel};
+use crate::cube::CubeState;
+use crate::history::{format_time, format_time_aligned, Penalty, SolveResult, Summary, TIME_WIDTH};
+use crate::ring_buffer::RingBuffer;
+use crate::scramble::{Puzzle, Scramble, ScrambleMove, ScrambleRng, ScrambleSource, ScrambleStats};
+use crate::text_input::TextInput;
+
+mod clipboard;
+mod config;
+mod cube;
+mod history;
+mod log;
+mod ring_buffer;
+mod scramble;
+mod solver;
+mod sound;
+mod stats;
+mod store;
+mod text_input;
 
 const INSPECT_DURATION: Duration = Duration::from_secs(15);
-const SCRAMBLE_MOVES: usize = 40;
+/// Lines drawn on with `--no-alt-screen`, at most the height of the terminal.
+const INLINE_HEIGHT: u16 = 20;
+/// Below this size only [`state_glyph`] is shown, the full view wouldn't fit.
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = 4;
+/// Width of the stats column with `--wide-layout`, the stats box with a margin.
+const STATS_COLUMN: u16 = 44;
+/// Pauses between solves longer than this count as breaks, not as the practice pace.
+const BREAK_AFTER: Duration = Duration::from_secs(10 * 60);
+/// Inspecting over time up to this long is a +2, any longer a DNF.
+const INSPECT_OVERTIME_DNF: Duration = Duration::from_secs(2);
+/// How long before a warn level is reached the colors start fading towards it.
+const INSPECT_WARN_FADE: Duration = Duration::from_secs(3);
+const HISTOGRAM_BUCKET_STEP: Duration = Duration::from_millis(500);
+const MAX_RECONSTRUCTION_LEN: usize = 4000;
+const FLASH_DURATION: Duration = Duration::from_millis(100);
+/// How often the screensaver cycles the scramble colors.
+const SCREENSAVER_STEP: Duration = Duration::from_secs(1);
+/// Number of solves shown on the done screen.
+const RECENT_SOLVES: usize = 5;
+/// Number of solves in the graph of the stats overlay.
+const GRAPH_SOLVES: usize = 30;
+const GRAPH_HEIGHT: u16 = 3;
+/// Bars for `--ascii`.
+const ASCII_BARS: symbols::bar::Set = symbols::bar::Set {
+    full: "#",
+    seven_eighths: "#",
+    three_quarters: "=",
+    five_eighths: "=",
+    half: "-",
+    three_eighths: "-",
+    one_quarter: ".",
+    one_eighth: ".",
+    empty: " ",
+};
+/// Borders for `--ascii`.
+const ASCII_BORDER: symbols::border::Set = symbols::border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
 
 #[derive(Clone, Debug, Default)]
 struct App {
+    config: Config,
     color_bg: bool,
     state: State,
+    scrambles: ScrambleSource,
+    history: Vec<SolveResult>,
+    /// Index of the first solve of this session in `history`.
+    session_start: usize,
+    greeting: Option<Summary>,
+    show_stats: bool,
+    /// Index of the first solve of the current `--target` set in `history`.
+    set_start: usize,
+    /// Completed `--target` sets as ranges of `history`.
+    sets: Vec<Range<usize>>,
+    /// The scramble replaced by the last rescramble and its `scramble_index`, so both can be
+    /// restored.
+    prev_scramble: Option<(Scramble, usize)>,
+    /// Asking for confirmation before discarding something.
+    confirm: Option<Confirm>,
+    show_list: bool,
+    show_scramble_stats: bool,
+    show_clock: bool,
+    done_focus: DoneFocus,
+    /// Counts of the scrambles shown this session.
+    scramble_stats: ScrambleStats,
+    /// The last idle scramble seen by `update`, to only count it and build its net once.
+    seen_scramble: Option<Scramble>,
+    /// Which draw from the scramble rng the current scramble is, or how many scrambles were shown
+    /// for sources that don't redraw. With `--seed` it identifies the scramble together with the
+    /// seed.
+    scramble_index: usize,
+    /// The cube after the current scramble, shown with `--net`.
+    net: Option<CubeState>,
+    /// A rotation shown before the current scramble with `--scramble-rotation`.
+    rotation: Option<String>,
+    /// Moves of an optimal solution of the current scramble, if it could be solved.
+    solution_hint: Option<usize>,
+    /// Editing the reconstruction of the last solve.
+    reconstruction: Option<TextInput>,
+    /// Ring the bell on the next frame.
+    ring_bell: bool,
+    /// Since when the screen is inverted to signal the end of a solve.
+    flash_since: Option<Instant>,
+    /// When the history was last saved, or attempted to.
+    last_save: Option<Instant>,
+    /// Changes to the history that failed to save.
+    unsaved: bool,
+    /// The history can't be written, so solves are only kept in memory and saving isn't tried.
+    read_only: bool,
+    /// Warn about `read_only` in the footer, until the first key press after the greeting.
+    read_only_warning: bool,
+    /// Incremented on every input event and state change, to detect when to redraw.
+    generation: u64,
+    last_frame: Option<FrameKey>,
+    /// The scramble of the current or last solve.
+    solving_scramble: Option<Scramble>,
+    /// How long inspection of the solve in progress went over time with `--inspection-overtime`.
+    overtime: Option<Duration>,
+    /// Memorization time of the blind solve in progress.
+    memo: Option<Duration>,
+    /// The solve waiting for `--confirm-solved`, not in the history yet.
+    unconfirmed: Option<SolveResult>,
+    /// Inspection time used for the solve in progress.
+    inspection: Option<Duration>,
+    /// Since when the solve in progress is paused with `--allow-pause`.
+    paused_at: Option<Instant>,
+    /// Whether the solve in progress was paused at some point.
+    was_paused: bool,
+    /// A message shown on the idle screen until the next input.
+    notice: Option<&'static str>,
+    /// The last input or state change, to start the screensaver.
+    idle_since: Option<Instant>,
+    /// The highlighted move when stepping through the scramble.
+    step: Option<usize>,
+    /// When the last solve was done, to ignore an eager space press right after.
+    done_since: Option<Instant>,
+    /// When the done screen continues by itself with `--auto-next`, until a key is pressed.
+    auto_next_at: Option<Instant>,
+    /// Final times of the last session solves in milliseconds, 0 for DNFs, for the graph.
+    graph: RingBuffer<u64>,
+    /// Consecutive solves under `--streak` this session.
+    streak: usize,
+    /// The longest `--streak` run this session, or of all time with `--persist-streak`.
+    best_streak: usize,
+    /// Scrambles retired this session by solving, DNFing or skipping them.
+    attempts: usize,
+    /// The stats histogram of the session and the bucket it was counted with. Cleared when the
+    /// session solves change, so it isn't recounted on every frame.
+    histogram: Option<(Duration, Vec<(Duration, usize)>)>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum State {
-    Idle(Scramble),
-    Inspecting(Instant),
-    Solving(Instant),
-    Done(Duration),
+/// What is shown large on the done screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum DoneFocus {
+    #[default]
+    Single,
+    Ao5,
+    Ao12,
 }
 
-impl Default for State {
-    fn default() -> Self {
-        Self::Idle(Scramble::random())
+impl DoneFocus {
+    fn next(self) -> Self {
+        match self {
+            Self::Single => Self::Ao5,
+            Self::Ao5 => Self::Ao12,
+            Self::Ao12 => Self::Single,
+        }
     }
-}
 
-impl State {
-    fn is_idle(&self) -> bool {
-        matches!(self, State::Idle(_))
+    /// The number of solves of the focused average.
+    fn average(self) -> Option<usize> {
+        match self {
+            Self::Single => None,
+            Self::Ao5 => Some(5),
+            Self::Ao12 => Some(12),
+        }
     }
+}
 
-    fn next(&mut self) {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Confirm {
+    /// Quit, since there are solves this session.
+    Quit,
+    /// Start a new session.
+    ResetSession,
+    /// Record the last solve with `--confirm-solved`, as a DNF when answered with no.
+    Solved,
+}
+
+impl Confirm {
+    fn prompt(&self) -> &'static str {
         match self {
-            Self::Idle(_) => {
-                *self = Self::Inspecting(Instant::now());
-            }
-            Self::Inspecting(_) => {
-                *self = State::Solving(Instant::now());
-            }
-            Self::Solving(start) => {
-                let duration = Instant::now().duration_since(*start);
-                *self = State::Done(duration);
-            }
-            State::Done(_) => {
-                *self = State::Idle(Scramble::random());
-            }
+            Self::Quit => "Quit? (y/n)",
+            Self::ResetSession => "Start a new session? (y/n)",
+            Self::Solved => "Solved? (y/n)",
         }
     }
 }
 
+/// Everything that can change what is rendered. Drawing is skipped while it stays the same,
+/// which avoids needless output over slow connections.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-struct Scramble {
-    moves: [Move; SCRAMBLE_MOVES],
+struct FrameKey {
+    generation: u64,
+    /// The displayed time in milliseconds.
+    time_ms: Option<u128>,
+    flash: bool,
+    screensaver_phase: Option<u64>,
+    /// The minute of the day while the clock is shown.
+    clock_minute: Option<u32>,
 }
 
-impl Default for Scramble {
-    fn default() -> Self {
-        Self::random()
+impl FrameKey {
+    fn new(app: &App) -> Self {
+        let now = Instant::now();
+        let time_ms = match app.state {
+            State::Inspecting(start) => Some(now.duration_since(start).as_millis()),
+            State::Solving(start) => Some(app.solve_elapsed(start, now).as_millis()),
+            _ => None,
+        };
+        Self {
+            generation: app.generation,
+            time_ms,
+            flash: app
+                .flash_since
+                .is_some_and(|s| s.elapsed() < FLASH_DURATION),
+            screensaver_phase: app.screensaver_phase(),
+            clock_minute: app.show_clock.then(|| {
+                let now = chrono::Local::now();
+                now.hour() * 60 + now.minute()
+            }),
+        }
     }
 }
 
-impl Scramble {
-    pub fn random() -> Self {
-        let mut rng = rand::thread_rng();
-        let mut moves = [Move(0); SCRAMBLE_MOVES];
-        let mut prev_dirs = PrevDirs(0);
-        for mov in &mut moves {
-            *mov = Move::random(&mut rng, prev_dirs);
-            prev_dirs.update(mov.dir());
+impl App {
+    fn new(config: Config, history: Vec<SolveResult>, mut scrambles: ScrambleSource) -> Self {
+        let greeting = (!history.is_empty()).then(|| Summary::new(&history));
+        let mut app = Self {
+            config,
+            state: State::Idle(scrambles.next()),
+            scrambles,
+            session_start: history.len(),
+            set_start: history.len(),
+            history,
+            greeting,
+            idle_since: Some(Instant::now()),
+            graph: RingBuffer::new(GRAPH_SOLVES),
+            ..Default::default()
+        };
+        app.recount_streaks();
+        app
+    }
+
+    /// Marks a change that needs a redraw and counts as activity.
+    fn touch(&mut self) {
+        self.generation += 1;
+        self.idle_since = Some(Instant::now());
+    }
+
+    /// How many steps the scramble colors have been cycled, once idle for long enough.
+    fn screensaver_phase(&self) -> Option<u64> {
+        let after = self.config.screensaver?;
+        if !self.state.is_idle() || self.greeting.is_some() {
+            return None;
         }
-        Self { moves }
+        let idle = self.idle_since?.elapsed().checked_sub(after)?;
+        Some((idle.as_millis() / SCREENSAVER_STEP.as_millis()) as u64)
     }
-}
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-struct Move(u8);
+    /// The scramble of a list to continue with next session, the current one unless it was
+    /// already solved.
+    fn resume_index(&self) -> Option<usize> {
+        let (pos, len) = self.scrambles.position()?;
+        Some(match self.state {
+            State::Idle(_) | State::Inspecting(_) | State::Solving(_) => pos - 1,
+            State::Done(_) | State::Summary { .. } => pos % len,
+        })
+    }
 
-impl std::fmt::Display for Move {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.dir() {
-            Dir::Front => f.write_char('F')?,
-            Dir::Back => f.write_char('B')?,
-            Dir::Left => f.write_char('L')?,
-            Dir::Right => f.write_char('R')?,
-            Dir::Up => f.write_char('U')?,
-            Dir::Down => f.write_char('D')?,
-        }
-        match self.modifier() {
-            Mod::Forward => f.write_char(' ')?,
-            Mod::Reverse => f.write_char('\'')?,
-            Mod::Double => f.write_char('2')?,
-        }
-        Ok(())
-    }
-}
-
-impl Move {
-    #[rustfmt::skip]
-    const DOUBLE: u8   = 0b1000_0000;
-    #[rustfmt::skip]
-    const REVERSE: u8  = 0b0100_0000;
-    #[rustfmt::skip]
-    const DIR_MASK: u8 = 0b0011_1111;
-
-    pub fn random(rng: &mut impl Rng, prev_dirs: PrevDirs) -> Self {
-        let mut mov = 0;
-
-        let num_dirs = 6 - prev_dirs.0.count_ones() as u8;
-        let mut dir: u8 = rng.gen_range(0..num_dirs);
-
-        for i in 0..6 {
-            let bit = 1 << i;
-            if !prev_dirs.get(bit) {
-                if dir == 0 {
-                    mov |= bit;
-                    break;
-                }
+    fn session(&self) -> &[SolveResult] {
+        &self.history[self.session_start..]
+    }
+
+    /// Saves the history. If that fails, the next autosave tries again.
+    fn save(&mut self) {
+        if self.read_only {
+            return;
+        }
+        self.last_save = Some(Instant::now());
+        self.unsaved = store::open(&self.config).save(&self.history).is_err();
+        if !self.unsaved {
+            _ = history::clear_journal();
+        }
+    }
+
+    /// Journals and saves the last solve after it was recorded or changed.
+    fn save_last(&mut self) {
+        if let (false, Some(solve)) = (self.read_only, self.history.last()) {
+            // only a fallback if the save fails, which is reported then
+            _ = history::append_journal(solve);
+        }
+        self.save();
+    }
 
-                dir -= 1;
+    /// Toggles the penalty of the solve that was just completed.
+    fn toggle_penalty(&mut self, penalty: Penalty) {
+        if let Some(solve) = self.history.last_mut() {
+            solve.toggle_penalty(penalty);
+            self.save_last();
+            self.recount_streaks();
+            self.histogram = None;
+            self.graph.clear();
+            let start = self.session().len().saturating_sub(GRAPH_SOLVES);
+            for i in self.session_start + start..self.history.len() {
+                self.graph.push(graph_value(&self.history[i]));
             }
         }
+    }
+
+    /// Extends or ends the `--streak` run with a new solve.
+    fn update_streaks(&mut self, solve: &SolveResult) {
+        let Some(target) = self.config.streak else {
+            return;
+        };
+        if solve.warmup {
+            return;
+        }
+        self.streak = match stats::beats(solve, target) {
+            true => self.streak + 1,
+            false => 0,
+        };
+        self.best_streak = self.best_streak.max(self.streak);
+    }
+
+    /// Counts the `--streak` runs from scratch, after a solve already counted changed.
+    fn recount_streaks(&mut self) {
+        let Some(target) = self.config.streak else {
+            return;
+        };
+        let solves = match self.config.persist_streak {
+            true => &self.history[..],
+            false => self.session(),
+        };
+        let best = stats::best_streak(solves, target);
+        self.streak = stats::streak(self.session(), target);
+        self.best_streak = best;
+    }
 
-        let modifier: u8 = rng.gen_range(0..3);
-        match modifier {
-            0 => (),
-            1 => mov |= Self::REVERSE,
-            2 | _ => mov |= Self::DOUBLE,
+    fn next(&mut self) {
+        self.auto_next_at = None;
+        match self.state {
+            State::Idle(scramble) => {
+                self.solving_scramble = Some(scramble);
+                self.inspection = None;
+                self.paused_at = None;
+                self.was_paused = false;
+            }
+            // stopping while paused ends the solve at the time it was paused
+            State::Solving(_) if self.paused_at.is_some() => self.toggle_pause(),
+            State::Summary { .. } => self.set_start = self.history.len(),
+            State::Inspecting(_) if self.config.inspect_only => {
+                self.state = State::Idle(self.scrambles.next());
+                return;
+            }
+            // memorization counts towards the time of a blind solve
+            State::Inspecting(start) if self.config.blind => {
+                self.memo = Some(start.elapsed());
+                self.state = State::Solving(start);
+                return;
+            }
+            State::Inspecting(start) => {
+                self.inspection = Some(start.elapsed());
+                if self.config.inspection_overtime {
+                    self.overtime = start.elapsed().checked_sub(INSPECT_DURATION);
+                }
+            }
+            _ => (),
         }
 
-        Self(mov)
+        // solves are only recorded here, on the transition into `Done`, never while in it
+        self.state.next(|| self.scrambles.next());
+        let overtime = match self.state {
+            State::Done(_) => self.overtime.take(),
+            _ => None,
+        };
+        match self.state {
+            // most likely an accidental double press, no real solve is that fast
+            State::Done(duration) if duration < self.config.min_solve_time => {
+                let scramble = self
+                    .solving_scramble
+                    .unwrap_or_else(|| self.scrambles.next());
+                self.state = State::Idle(scramble);
+                self.notice = Some("too fast, ignored");
+            }
+            State::Done(duration) => {
+                let mut solve = SolveResult::new(duration);
+                solve.warmup = self.config.skip_warmup && self.session().is_empty();
+                solve.memo = self.memo.take();
+                solve.inspection = self.inspection.take();
+                solve.paused = std::mem::take(&mut self.was_paused);
+                if let Some(overtime) = overtime {
+                    solve.penalty = match overtime <= INSPECT_OVERTIME_DNF {
+                        true => Penalty::Plus2,
+                        false => Penalty::Dnf,
+                    };
+                }
+                solve.session = self.config.session_name.clone();
+                solve.scramble = scramble_text(self);
+                match self.config.confirm_solved {
+                    true => {
+                        self.unconfirmed = Some(solve);
+                        self.confirm = Some(Confirm::Solved);
+                    }
+                    false => self.record(solve),
+                }
+                self.done_since = Some(Instant::now());
+                // the bell stands in for a sound that can't be played
+                let played = self.config.sound.as_deref().map(sound::play);
+                self.ring_bell = self.config.feedback.bell() || played == Some(false);
+                if self.config.feedback.flash() {
+                    self.flash_since = Some(Instant::now());
+                }
+            }
+            State::Idle(_) if self.set_complete() => {
+                self.sets.push(self.set_start..self.history.len());
+                self.state = State::Summary {
+                    set: self.sets.len() - 1,
+                    review: false,
+                };
+            }
+            _ => (),
+        }
     }
 
-    pub fn dir(&self) -> Dir {
-        let dir = self.0 & Self::DIR_MASK;
-        // SAFETY: Dir is repr(u8)
-        unsafe { std::mem::transmute(dir) }
+    /// Starts inspecting a fresh scramble in one step, from idle or done. A completed set still
+    /// shows its summary first.
+    fn go(&mut self) {
+        match self.state {
+            State::Idle(_) => {
+                self.attempts += 1;
+                self.state = State::Idle(self.scrambles.next());
+            }
+            State::Done(_) => self.next(),
+            _ => return,
+        }
+        if let State::Idle(scramble) = self.state {
+            see_scramble(self, scramble);
+            self.next();
+        }
     }
 
-    pub fn modifier(&self) -> Mod {
-        if (self.0 & Self::DOUBLE) != 0 {
-            return Mod::Double;
+    /// Abandons the current attempt for a fresh scramble. Leaving a done solve goes through
+    /// `next`, so a completed set still shows its summary.
+    fn cancel(&mut self) {
+        match self.state {
+            State::Summary { .. } => (),
+            // a done solve was already counted when it was recorded
+            State::Done(_) => self.next(),
+            _ => {
+                self.attempts += 1;
+                self.state = State::Idle(self.scrambles.next());
+            }
         }
-        match (self.0 & Self::REVERSE) != 0 {
-            true => Mod::Reverse,
-            false => Mod::Forward,
+    }
+
+    /// Skips the idle scramble, which counts as an attempt until it is restored.
+    fn rescramble(&mut self) {
+        // only the first relay scramble would be restored
+        let relay = matches!(self.scrambles, ScrambleSource::Relay { .. });
+        if let (State::Idle(scramble), false) = (self.state, relay) {
+            self.prev_scramble = Some((scramble, self.scramble_index));
         }
+        self.attempts += 1;
+        self.state = State::Idle(self.scrambles.next());
     }
-}
 
-#[derive(Clone, Copy)]
-struct PrevDirs(u8);
+    /// Restores the scramble replaced by the last [`App::rescramble`].
+    fn undo_rescramble(&mut self) {
+        if let Some((prev, index)) = self.prev_scramble.take() {
+            self.attempts -= 1;
+            self.state = State::Idle(prev);
+            see_scramble(self, prev);
+            self.scramble_index = index;
+        }
+    }
 
-impl PrevDirs {
-    fn update(&mut self, dir: Dir) {
-        self.0 &= match dir {
-            Dir::Front | Dir::Back => Dir::Front as u8 | Dir::Back as u8,
-            Dir::Left | Dir::Right => Dir::Left as u8 | Dir::Right as u8,
-            Dir::Up | Dir::Down => Dir::Up as u8 | Dir::Down as u8,
+    /// Pauses or resumes the solve in progress. The start is moved by the paused time when
+    /// resuming, so the elapsed time is still measured from it.
+    fn toggle_pause(&mut self) {
+        let State::Solving(start) = self.state else {
+            return;
         };
-        self.0 |= dir as u8;
+        match self.paused_at.take() {
+            Some(at) => self.state = State::Solving(start + at.elapsed()),
+            None => {
+                self.paused_at = Some(Instant::now());
+                self.was_paused = true;
+            }
+        }
+    }
+
+    /// Time spent solving from `start` until `now`, not counting the current pause.
+    fn solve_elapsed(&self, start: Instant, now: Instant) -> Duration {
+        self.paused_at.unwrap_or(now).duration_since(start)
+    }
+
+    /// Adds a finished solve to the history.
+    fn record(&mut self, solve: SolveResult) {
+        self.update_streaks(&solve);
+        self.attempts += 1;
+        self.graph.push(graph_value(&solve));
+        self.history.push(solve);
+        self.histogram = None;
+        self.save_last();
+        self.auto_next_at = self.config.auto_next.map(|after| Instant::now() + after);
     }
 
-    fn get(&self, bit: u8) -> bool {
-        (self.0 & bit) != 0
+    /// Records the solve waiting for `--confirm-solved`, as a DNF if the cube wasn't solved.
+    fn confirm_solved(&mut self, solved: bool) {
+        self.confirm = None;
+        if let Some(mut solve) = self.unconfirmed.take() {
+            if !solved {
+                solve.penalty = Penalty::Dnf;
+            }
+            self.record(solve);
+        }
+    }
+
+    /// Whether the done screen was only just shown, too briefly to have read the time.
+    fn in_done_grace(&self) -> bool {
+        matches!(self.state, State::Done(_))
+            && self
+                .done_since
+                .is_some_and(|since| since.elapsed() < self.config.done_grace)
+    }
+
+    /// Starts over with an empty session, the solves of the old one stay in the saved history.
+    fn reset_session(&mut self) {
+        self.session_start = self.history.len();
+        self.set_start = self.history.len();
+        self.sets.clear();
+        self.attempts = 0;
+        self.prev_scramble = None;
+        self.recount_streaks();
+        self.histogram = None;
+        self.graph.clear();
+        self.state = State::Idle(self.scrambles.next());
+    }
+
+    /// Counts the histogram again if the session or the bucket changed.
+    fn refresh_histogram(&mut self) {
+        let bucket = self.config.histogram_bucket;
+        if self.histogram.as_ref().is_some_and(|(b, _)| *b == bucket) {
+            return;
+        }
+        self.histogram = Some((bucket, stats::histogram(self.session(), bucket)));
+    }
+
+    fn set_complete(&self) -> bool {
+        let len = self.history.len() - self.set_start;
+        self.config.target.is_some_and(|target| len >= target)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Idle(Scramble),
+    Inspecting(Instant),
+    Solving(Instant),
+    Done(Duration),
+    /// A completed `--target` set, optionally showing the list of its solves.
+    Summary {
+        set: usize,
+        review: bool,
+    },
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::Idle(Scramble::random())
     }
 }
 
-#[repr(u8)]
-#[rustfmt::skip]
-enum Dir {
-    Front = 1 << 0,
-    Back  = 1 << 1,
-    Left  = 1 << 2,
-    Right = 1 << 3,
-    Up    = 1 << 4,
-    Down  = 1 << 5,
+impl std::fmt::Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Idle(_) => f.write_str("idle"),
+            Self::Inspecting(_) => f.write_str("inspecting"),
+            Self::Solving(_) => f.write_str("solving"),
+            Self::Done(duration) => write!(f, "done ({})", format_time(*duration)),
+            Self::Summary { set, .. } => write!(f, "summary of set {}", set + 1),
+        }
+    }
 }
 
-enum Mod {
-    Forward,
-    Reverse,
-    Double,
+impl State {
+    fn is_idle(&self) -> bool {
+        matches!(self, State::Idle(_))
+    }
+
+    fn next(&mut self, next_scramble: impl FnOnce() -> Scramble) {
+        match self {
+            Self::Idle(_) => {
+                *self = Self::Inspecting(Instant::now());
+            }
+            Self::Inspecting(_) => {
+                *self = State::Solving(Instant::now());
+            }
+            Self::Solving(start) => {
+                let duration = Instant::now().duration_since(*start);
+                *self = State::Done(duration);
+            }
+            State::Done(_) | State::Summary { .. } => {
+                *self = State::Idle(next_scramble());
+            }
+        }
+    }
 }
 
 fn main() {
@@ -200,49 +656,396 @@ fn main() {
 }
 
 fn run() -> Result<(), Box<dyn Error>> {
+    let config = Config::load()?;
+    if let Some(count) = config.bench_gen {
+        let len = config
+            .scramble_length
+            .unwrap_or(scramble::default_scramble_length(config.puzzle));
+        let mut rng = ScrambleRng::new(config.rng, config.seed)?;
+        let start = Instant::now();
+        for _ in 0..count {
+            std::hint::black_box(Scramble::random_with(&mut rng, config.puzzle, len));
+        }
+        let secs = start.elapsed().as_secs_f64();
+        println!(
+            "n={count} secs={secs:.6} per_sec={:.0}",
+            count as f64 / secs
+        );
+        return Ok(());
+    }
+    let store = store::open(&config);
+    let mut history = store.load()?;
+    let read_only = !history::writable();
+    if history::replay_journal(&mut history)? > 0 && !read_only {
+        store.save(&history)?;
+        history::clear_journal()?;
+    }
+    if let (Some(path), ExportFormat::CsTimer) = (&config.export, config.export_format) {
+        std::fs::write(path, history::export_cstimer(&history))
+            .map_err(|e| format!("error writing {}: {e}", path.display()))?;
+        return Ok(());
+    }
+    if let Some(path) = &config.export {
+        let meta = history::ExportMeta {
+            version: env!("CARGO_PKG_VERSION"),
+            puzzle: config.puzzle.to_string(),
+            scramble_length: config
+                .scramble_length
+                .unwrap_or(scramble::default_scramble_length(config.puzzle)),
+            seed: config.seed,
+            rng: config.rng.to_string(),
+        };
+        return history::export(path, &meta, &history);
+    }
+    if config.oneshot_stats {
+        println!("{}", oneshot_stats(&history));
+        return Ok(());
+    }
+    let relay = !config.relay.is_empty();
+    let random = !relay && config.alg.is_none() && config.scramble_file.is_none();
+    if config.case.is_some() && !(random && config.puzzle == Puzzle::ThreeByThree) {
+        return Err("`--case` only works with random 3x3 scrambles".into());
+    }
+    let mut resume_changed = false;
+    let scrambles = match (config.alg, &config.scramble_file) {
+        (Some(_), Some(_)) => return Err("`--alg` and `--scramble-file` can't be combined".into()),
+        _ if relay && (config.alg.is_some() || config.scramble_file.is_some()) => {
+            return Err("`--relay` can't be combined with `--alg` or `--scramble-file`".into());
+        }
+        (Some(alg), None) => ScrambleSource::Alg(alg),
+        (None, Some(path)) => {
+            let mut scrambles = ScrambleSource::load(path)?;
+            resume_changed = scrambles.resume(path, history::data_dir().as_deref());
+            scrambles
+        }
+        (None, None) if relay => ScrambleSource::Relay {
+            puzzles: config.relay.clone(),
+            scrambles: Vec::new(),
+            rng: Box::new(ScrambleRng::new(config.rng, config.seed)?),
+        },
+        (None, None) => {
+            let rng = ScrambleRng::new(config.rng, config.seed)?;
+            match config.case {
+                Some(case) => ScrambleSource::Case {
+                    case,
+                    rng: Box::new(rng),
+                },
+                None => ScrambleSource::random(
+                    config.puzzle,
+                    config
+                        .scramble_length
+                        .unwrap_or(scramble::default_scramble_length(config.puzzle)),
+                    rng,
+                ),
+            }
+        }
+    };
+    let mut app = App::new(config, history, scrambles);
+    app.read_only = read_only;
+    app.read_only_warning = read_only;
+    if resume_changed {
+        app.notice = Some("scramble file changed, starting over");
+    }
+
+    if let Some(path) = &app.config.log {
+        log::open(path)?;
+    }
+
+    // there is nothing to draw on when piped, so just print a scramble, compact for scripts
+    if !std::io::stdout().is_terminal() {
+        match (&app.scrambles, app.state) {
+            (
+                ScrambleSource::Relay {
+                    puzzles, scrambles, ..
+                },
+                _,
+            ) => {
+                for (puzzle, scramble) in puzzles.iter().zip(scrambles) {
+                    println!("{puzzle}: {scramble:#}");
+                }
+            }
+            (_, State::Idle(scramble)) => println!("{scramble:#}"),
+            _ => (),
+        }
+        return Ok(());
+    }
+
+    let mouse = app.config.mouse;
+    let alt_screen = !app.config.no_alt_screen;
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        _ = restore_terminal(&mut std::io::stdout(), mouse, alt_screen);
+        default_hook(info);
+    }));
+
     crossterm::terminal::enable_raw_mode()?;
     let mut stdout = std::io::stdout();
-    crossterm::execute!(stdout, EnterAlternateScreen)?;
+    if alt_screen {
+        crossterm::execute!(stdout, EnterAlternateScreen)?;
+    }
+    if mouse {
+        crossterm::execute!(stdout, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
-    let mut terminal = ratatui::Terminal::new(backend)?;
-
-    let mut app = App::default();
+    // inline, the last frame stays in the scrollback after quitting
+    let viewport = match alt_screen {
+        true => Viewport::Fullscreen,
+        false => Viewport::Inline(INLINE_HEIGHT.min(crossterm::terminal::size()?.1)),
+    };
+    let mut terminal = ratatui::Terminal::with_options(backend, TerminalOptions { viewport })?;
 
     let res = loop {
+        let prev = app.state;
         match input(&mut app) {
-            Ok(c) if c == false => break Ok(()),
+            Ok(false) => break Ok(()),
             Ok(_) => (),
             Err(e) => break Err(e),
         }
 
         update(&mut app);
 
+        if log::enabled() && std::mem::discriminant(&prev) != std::mem::discriminant(&app.state) {
+            log_transition(prev, app.state);
+        }
+
+        if std::mem::take(&mut app.ring_bell) {
+            if let Err(e) = terminal.backend_mut().write_all(b"\x07") {
+                break Err(e.into());
+            }
+        }
+
+        let key = FrameKey::new(&app);
+        if app.last_frame == Some(key) {
+            continue;
+        }
+        app.last_frame = Some(key);
+
         let res = terminal.draw(|frame| ui(&mut app, frame));
         if let Err(e) = res {
             break Err(e.into());
         }
     };
 
+    if !alt_screen {
+        let area = terminal.get_frame().size();
+        terminal.set_cursor(0, area.bottom().saturating_sub(1))?;
+    }
+    restore_terminal(terminal.backend_mut(), mouse, alt_screen)?;
+    res?;
+    // the scramble file position is kept in the data directory too, so it can't be saved either
+    if app.read_only {
+        return Ok(());
+    }
+    if let (Some(path), Some(index)) = (&app.config.scramble_file, app.resume_index()) {
+        // only the position is lost, the scramble file itself is still there
+        _ = app
+            .scrambles
+            .save_position(path, index, history::data_dir().as_deref());
+    }
+    store::open(&app.config).save(&app.history)
+}
+
+fn restore_terminal(
+    out: &mut impl std::io::Write,
+    mouse: bool,
+    alt_screen: bool,
+) -> std::io::Result<()> {
     crossterm::terminal::disable_raw_mode()?;
-    crossterm::execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    res
+    if mouse {
+        crossterm::execute!(out, DisableMouseCapture)?;
+    }
+    match alt_screen {
+        true => crossterm::execute!(out, LeaveAlternateScreen),
+        false => writeln!(out),
+    }
+}
+
+fn log_transition(prev: State, next: State) {
+    let elapsed = match prev {
+        State::Inspecting(start) | State::Solving(start) => {
+            format!(" after {}", format_time(start.elapsed()))
+        }
+        _ => String::new(),
+    };
+    log::write(format_args!("{prev} -> {next}{elapsed}"));
 }
 
 fn input(app: &mut App) -> Result<bool, Box<dyn Error>> {
     if crossterm::event::poll(Duration::from_millis(1))? {
         let event = crossterm::event::read()?;
+        app.touch();
+        // the greeting hides the idle screen, keep a notice for after it
+        if app.greeting.is_none() {
+            app.notice = None;
+        }
+        // a left click acts like space, except while typing
+        let event = match event {
+            Event::Mouse(m)
+                if m.kind == MouseEventKind::Down(MouseButton::Left)
+                    && app.reconstruction.is_none() =>
+            {
+                Event::Key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE))
+            }
+            e => e,
+        };
         if let Event::Key(k) = event {
             if k.kind == KeyEventKind::Press {
+                // raw mode swallows the signal, quit without asking like most terminal programs,
+                // the history is saved on the way out
+                if k.code == KeyCode::Char('c') && k.modifiers.contains(KeyModifiers::CONTROL) {
+                    return Ok(false);
+                }
+                app.auto_next_at = None;
+                if app.greeting.is_some() && k.code != KeyCode::Char('q') {
+                    app.greeting = None;
+                    return Ok(true);
+                }
+                app.read_only_warning = false;
+
+                // unlike the other questions, this one has to be answered
+                if app.confirm == Some(Confirm::Solved) {
+                    match k.code {
+                        KeyCode::Char('y') => app.confirm_solved(true),
+                        KeyCode::Char('n') => app.confirm_solved(false),
+                        _ => (),
+                    }
+                    return Ok(true);
+                }
+                if let Some(confirm) = app.confirm.take() {
+                    if k.code == KeyCode::Char('y') {
+                        match confirm {
+                            Confirm::Quit => return Ok(false),
+                            Confirm::ResetSession => app.reset_session(),
+                            Confirm::Solved => (),
+                        }
+                    }
+                    return Ok(true);
+                }
+
+                if let Some(editor) = &mut app.reconstruction {
+                    match editor.handle_key(k) {
+                        text_input::Action::None => (),
+                        text_input::Action::Submit => {
+                            let text = editor.text.trim().to_string();
+                            app.reconstruction = None;
+                            if let Some(solve) = app.history.last_mut() {
+                                solve.reconstruction = (!text.is_empty()).then_some(text);
+                                app.save_last();
+                            }
+                        }
+                        text_input::Action::Cancel => app.reconstruction = None,
+                    }
+                    return Ok(true);
+                }
+
                 match k.code {
+                    KeyCode::Char('q') if !app.session().is_empty() => {
+                        app.confirm = Some(Confirm::Quit);
+                    }
                     KeyCode::Char('q') => return Ok(false),
+                    KeyCode::Char('X') if !app.session().is_empty() => {
+                        app.confirm = Some(Confirm::ResetSession);
+                    }
                     KeyCode::Char('c') => {
                         app.color_bg = !app.color_bg;
                     }
-                    KeyCode::Char('r') if app.state.is_idle() => {
-                        app.state = State::Idle(Scramble::random());
+                    KeyCode::Char('p')
+                        if app.config.allow_pause && matches!(app.state, State::Solving(_)) =>
+                    {
+                        app.toggle_pause();
+                    }
+                    KeyCode::Char('p') => {
+                        app.config.palette = app.config.palette.next();
+                        // still applies to this session if it can't be stored
+                        _ = config::store("palette", app.config.palette.name());
+                    }
+                    KeyCode::Char('s') if !app.config.minimal => {
+                        app.show_stats = !app.show_stats;
+                    }
+                    KeyCode::Char('+') if app.show_stats => {
+                        app.config.histogram_bucket += HISTOGRAM_BUCKET_STEP;
                     }
-                    KeyCode::Char(' ') => app.state.next(),
-                    KeyCode::Backspace => app.state = State::Idle(Scramble::random()),
+                    KeyCode::Char('-') if app.show_stats => {
+                        let bucket = &mut app.config.histogram_bucket;
+                        let smaller = bucket.saturating_sub(HISTOGRAM_BUCKET_STEP);
+                        if smaller >= config::MIN_HISTOGRAM_BUCKET {
+                            *bucket = smaller;
+                        }
+                    }
+                    KeyCode::Char('2') if matches!(app.state, State::Done(_)) => {
+                        app.toggle_penalty(Penalty::Plus2);
+                    }
+                    KeyCode::Char('d') if matches!(app.state, State::Done(_)) => {
+                        app.toggle_penalty(Penalty::Dnf);
+                    }
+                    KeyCode::Char('R') if matches!(app.state, State::Done(_)) => {
+                        if let Some(solve) = app.history.last() {
+                            let text = solve.reconstruction.clone().unwrap_or_default();
+                            app.reconstruction = Some(TextInput::new(text, MAX_RECONSTRUCTION_LEN));
+                        }
+                    }
+                    KeyCode::Char('r') if app.state.is_idle() => app.rescramble(),
+                    KeyCode::Char('r') if matches!(app.state, State::Inspecting(_)) => {
+                        app.state = State::Inspecting(Instant::now());
+                    }
+                    KeyCode::Char('u') if app.state.is_idle() => app.undo_rescramble(),
+                    KeyCode::Char('n')
+                        if matches!(app.state, State::Summary { .. })
+                            || matches!(app.state, State::Done(_)) && !app.in_done_grace() =>
+                    {
+                        app.next();
+                    }
+                    KeyCode::Char('g') if !app.in_done_grace() => app.go(),
+                    KeyCode::Char('l') => {
+                        if let State::Summary { review, .. } = &mut app.state {
+                            *review = !*review;
+                        } else if !app.config.minimal {
+                            app.show_list = !app.show_list;
+                        }
+                    }
+                    KeyCode::Char('a') if !app.config.minimal => {
+                        app.done_focus = app.done_focus.next()
+                    }
+                    KeyCode::Char('F') => app.show_scramble_stats = !app.show_scramble_stats,
+                    KeyCode::Char('T') => app.show_clock = !app.show_clock,
+                    KeyCode::Char('Y') if matches!(app.state, State::Done(_)) => {
+                        if let Some(text) = share_text(app) {
+                            app.notice = match clipboard::copy(&mut std::io::stdout(), &text) {
+                                Ok(()) => Some("copied"),
+                                Err(_) => Some("copying failed"),
+                            };
+                        }
+                    }
+                    KeyCode::Left | KeyCode::Right => match &mut app.state {
+                        State::Summary { set, .. } => {
+                            *set = match k.code {
+                                KeyCode::Left => set.saturating_sub(1),
+                                _ => (*set + 1).min(app.sets.len() - 1),
+                            };
+                        }
+                        // a relay has no single list of moves to step through
+                        State::Idle(scramble)
+                            if !matches!(app.scrambles, ScrambleSource::Relay { .. })
+                                && !scramble.moves().is_empty() =>
+                        {
+                            let last = scramble.moves().len().saturating_sub(1);
+                            app.step = match (k.code, app.step) {
+                                (KeyCode::Left, Some(0) | None) => None,
+                                (KeyCode::Left, Some(step)) => Some(step - 1),
+                                (_, None) => Some(0),
+                                (_, Some(step)) => Some((step + 1).min(last)),
+                            };
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char(' ') => {
+                        let done = matches!(app.state, State::Done(_));
+                        let ignore = app.config.done_space_action == DoneSpaceAction::Ignore;
+                        if !(done && ignore || app.in_done_grace()) {
+                            app.next();
+                        }
+                    }
+                    KeyCode::Backspace => app.cancel(),
                     _ => (),
                 }
             }
@@ -252,132 +1055,1536 @@ fn input(app: &mut App) -> Result<bool, Box<dyn Error>> {
     Ok(true)
 }
 
+/// A single line like `best 9.87 | ao5 11.23 | n 142` over the whole history, for status bars.
+/// Missing stats are shown as `-`.
+fn oneshot_stats(history: &[SolveResult]) -> String {
+    let secs = |time: Duration| format!("{:.2}", time.as_secs_f64());
+    let best = stats::counted(history)
+        .filter_map(SolveResult::final_time)
+        .min()
+        .map_or_else(|| "-".into(), secs);
+    let ao5 = match stats::average(history, 5) {
+        Some(Some(time)) => secs(time),
+        Some(None) => "DNF".into(),
+        None => "-".into(),
+    };
+    let count = stats::counted(history).count();
+    format!("best {best} | ao5 {ao5} | n {count}")
+}
+
+/// The last solve and its scramble as one line, e.g. `11.230s — R U R' ...`.
+fn share_text(app: &App) -> Option<String> {
+    let solve = app.history.last()?;
+    Some(format!("{solve} — {}", scramble_text(app)?))
+}
+
+/// The scramble being solved on one line, each relay scramble prefixed with its puzzle.
+fn scramble_text(app: &App) -> Option<String> {
+    match &app.scrambles {
+        ScrambleSource::Relay {
+            puzzles, scrambles, ..
+        } => {
+            let scrambles: Vec<_> = puzzles
+                .iter()
+                .zip(scrambles)
+                .map(|(puzzle, scramble)| format!("{puzzle}: {scramble:#}"))
+                .collect();
+            Some(scrambles.join(" / "))
+        }
+        _ => Some(format!("{:#}", app.solving_scramble?)),
+    }
+}
+
+/// What the next solve has to beat for a new best ao5 of the session, with `--pb-target`.
+fn pb_target_line(app: &App) -> Option<Line<'static>> {
+    if !app.config.pb_target || app.config.minimal {
+        return None;
+    }
+    let best = stats::best_average(app.session(), 5)?;
+    let target = stats::ao5_target(app.session(), best)?;
+    let text = format!("beat {:.2} for a new ao5 PB", target.as_secs_f32());
+    Some(Line::from(Span::from(text).dim()))
+}
+
+/// A single character for the state, shown in a corner when the terminal is too small for
+/// anything else.
+fn state_glyph(state: &State) -> char {
+    match state {
+        State::Idle(_) => '●',
+        State::Inspecting(_) => 'I',
+        State::Solving(_) => 'S',
+        State::Done(_) => '✓',
+        State::Summary { .. } => '=',
+    }
+}
+
+/// `--idle-prompt`, or how the timer is started with the current options.
+fn idle_prompt(config: &Config) -> String {
+    match (&config.idle_prompt, config.mouse) {
+        (Some(prompt), _) => prompt.clone(),
+        (None, true) => "Press space or click to start".into(),
+        (None, false) => "Press space to start".into(),
+    }
+}
+
+fn graph_value(solve: &SolveResult) -> u64 {
+    solve.final_time().map_or(0, |t| t.as_millis() as u64)
+}
+
+/// Prepares what is shown along with an idle scramble, once per new scramble.
+fn see_scramble(app: &mut App, scramble: Scramble) {
+    if app.seen_scramble == Some(scramble) {
+        return;
+    }
+    app.seen_scramble = Some(scramble);
+    app.scramble_index = match app.scrambles.draws() {
+        Some(draws) => draws,
+        None => app.scramble_index + 1,
+    };
+    app.step = None;
+    match &app.scrambles {
+        ScrambleSource::Relay { scrambles, .. } => {
+            scrambles.iter().for_each(|s| app.scramble_stats.add(s));
+        }
+        _ => app.scramble_stats.add(&scramble),
+    }
+
+    let relay = matches!(app.scrambles, ScrambleSource::Relay { .. });
+    app.solution_hint = match relay {
+        true => None,
+        false => solver::solution_hint(&scramble, app.config.puzzle),
+    };
+    app.rotation = (app.config.scramble_rotation && !relay)
+        .then(|| cube::format_rotations(&cube::random_rotations(&mut rand::thread_rng())));
+    app.net = (app.config.net && !relay).then(|| {
+        let mut state = CubeState::scrambled(&scramble);
+        if app.config.random_orientation {
+            state.rotate_randomly(&mut rand::thread_rng());
+        }
+        state
+    });
+}
+
 fn update(app: &mut App) {
+    if let State::Idle(scramble) = app.state {
+        see_scramble(app, scramble);
+    }
+
+    let since_save = app.last_save.map_or(Duration::MAX, |t| t.elapsed());
+    if app.unsaved && since_save >= app.config.autosave_interval {
+        app.generation += 1;
+        app.save();
+    }
+
     match app.state {
         State::Idle(_) => (),
         State::Inspecting(start) => {
             let now = Instant::now();
             let duration = now.duration_since(start);
-            if duration > INSPECT_DURATION {
-                app.state = State::Solving(now);
+            let timeout = !app.config.blind && !app.config.inspection_overtime;
+            if duration > INSPECT_DURATION && timeout {
+                app.touch();
+                app.inspection = Some(duration);
+                app.state = match app.config.inspect_only {
+                    true => State::Idle(app.scrambles.next()),
+                    false => State::Solving(now),
+                };
             }
         }
         State::Solving(_) => (),
-        State::Done(_) => (),
+        State::Done(_) => {
+            if app.auto_next_at.is_some_and(|at| Instant::now() >= at) {
+                app.touch();
+                app.next();
+            }
+        }
+        State::Summary { .. } => (),
     }
 }
 
 fn ui(app: &mut App, frame: &mut Frame) {
-    match app.state {
-        State::Idle(scramble) => {
-            let mut lines = vec![
-                Line::from("Press space to start"),
-                Line::from(""),
+    // one instant for the whole frame, so every time shown in it agrees
+    let now = Instant::now();
+    let size = frame.size();
+    if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+        let glyph = match (app.config.ascii, state_glyph(&app.state)) {
+            (true, '●') => 'o',
+            (true, '✓') => 'D',
+            (_, glyph) => glyph,
+        };
+        frame.render_widget(Paragraph::new(glyph.to_string()), size);
+        return;
+    }
+
+    if let Some(summary) = app.greeting {
+        let lines = vec![
+            Line::from("Welcome back"),
+            Line::from(""),
+            Line::from(""),
+            Line::from(format!("{} solves today", summary.today)),
+            Line::from(format!("{} solves this week", summary.week)),
+        ];
+        centered_text(
+            frame,
+            frame.size(),
+            lines,
+            app.color_bg,
+            app.config.palette,
+            Color::Rgb(0xc0, 0xc0, 0xc0),
+            Color::Rgb(0x20, 0x20, 0x20),
+        );
+        return;
+    }
+
+    let (main, side) = columns(app, frame.size());
+    match app.state {
+        State::Idle(scramble) => {
+            let phase = app.screensaver_phase().map_or(0, |p| (p % 6) as u8);
+            let mut lines = vec![
+                Line::from(idle_prompt(&app.config)),
+                Line::from(app.notice.unwrap_or_default()),
                 Line::from(""),
             ];
-            for i in 0..2 {
-                let num_line_moves = SCRAMBLE_MOVES / 2;
-                let start = i * num_line_moves;
-                let end = (i + 1) * num_line_moves;
-                let mut scramble_line = Vec::with_capacity(SCRAMBLE_MOVES);
-                for mov in scramble.moves[start..end].iter() {
-                    let mut str = String::with_capacity(4);
-                    write!(&mut str, "{mov}").ok();
-                    let color_idx = (mov.dir() as u8).trailing_zeros() as u8;
-                    let color = Color::Indexed(color_idx + 1);
-                    let span = Span::styled(str, color);
-                    scramble_line.push(span);
-                    scramble_line.push(Span::from(" "));
+            if let ScrambleSource::Relay {
+                puzzles, scrambles, ..
+            } = &app.scrambles
+            {
+                for (puzzle, scramble) in puzzles.iter().zip(scrambles) {
+                    lines.push(Line::from(Span::from(puzzle.to_string()).dim()));
+                    scramble_lines(&mut lines, scramble, &app.config, phase, None);
                 }
-                scramble_line.pop();
-                lines.push(Line::from(scramble_line));
+            } else {
+                if let Some(rotation) = app.rotation.as_ref().filter(|r| !r.is_empty()) {
+                    lines.push(Line::from(Span::from(rotation.clone()).bold()));
+                    lines.push(Line::from(""));
+                }
+                scramble_lines(&mut lines, &scramble, &app.config, phase, app.step);
+                if let Some(step) = app.step {
+                    let len = scramble.moves().len();
+                    lines.push(Line::from(
+                        Span::from(format!("move {}/{len}", step + 1)).dim(),
+                    ));
+                }
+            }
+            // the difficulty estimate is meaningless for a mix of puzzles
+            if !app.config.minimal && app.config.relay.is_empty() {
+                lines.push(Line::from(
+                    Span::from(scramble::estimate_difficulty(&scramble).to_string()).dim(),
+                ));
+                if let Some(moves) = app.solution_hint {
+                    lines.push(Line::from(
+                        Span::from(format!("optimal solution: {moves} moves")).dim(),
+                    ));
+                }
+                if let Some((pos, len)) = app.scrambles.position() {
+                    lines.push(Line::from(format!("{pos}/{len}")));
+                }
+                if let (Some(seed), None) = (app.config.seed, app.scrambles.position()) {
+                    let index = app.scramble_index;
+                    lines.push(Line::from(
+                        Span::from(format!("seed {seed} #{index}")).dim(),
+                    ));
+                }
+            }
+            if let (false, Some(solve)) = (app.config.minimal, app.history.last()) {
                 lines.push(Line::from(""));
+                lines.push(Line::from(Span::from(format!("last {solve}")).dim()));
+            }
+            if let Some(line) = pb_target_line(app) {
+                lines.push(Line::from(""));
+                lines.push(line);
+            }
+            if let (false, Some(goal)) = (app.config.minimal, app.config.daily_goal) {
+                let today = history::count_today(&app.history);
+                lines.push(Line::from(
+                    Span::from(format!("{today}/{goal} today")).dim(),
+                ));
+            }
+            if let Some(state) = &app.net {
+                lines.push(Line::from(""));
+                lines.extend(net_lines(state, app.config.puzzle, app.config.ascii));
             }
 
             centered_text(
                 frame,
+                main,
                 lines,
                 app.color_bg,
+                app.config.palette,
                 Color::Rgb(0xc0, 0xc0, 0xc0),
                 Color::Rgb(0x20, 0x20, 0x20),
             );
         }
-        State::Inspecting(start) => {
-            let duration = Instant::now().duration_since(start);
-            let remaining = INSPECT_DURATION.saturating_sub(duration);
-            let secs = remaining.as_secs_f32();
+        State::Inspecting(start) if app.config.blind => {
+            let secs = now.duration_since(start).as_secs_f32();
             let lines = vec![
-                Line::from("Inspecting"),
+                Line::from("Memorizing"),
                 Line::from(""),
                 Line::from(""),
                 Line::from(format!("{secs:.3}s")),
             ];
+            let (fg, bg) = inspect_colors(&[], Duration::MAX);
+            centered_text(frame, main, lines, app.color_bg, app.config.palette, fg, bg);
+        }
+        State::Inspecting(start) => {
+            let duration = now.duration_since(start);
+            let remaining = INSPECT_DURATION.saturating_sub(duration);
+            let overtime = duration
+                .checked_sub(INSPECT_DURATION)
+                .filter(|_| app.config.inspection_overtime);
+            let time = match (overtime, app.config.inspection_countup) {
+                (Some(overtime), _) => format!("-{:.3}s", overtime.as_secs_f32()),
+                (None, true) => format!("{:.3}s", duration.min(INSPECT_DURATION).as_secs_f32()),
+                (None, false) => format!("{:.3}s", remaining.as_secs_f32()),
+            };
+            let mut lines = vec![Line::from("Inspecting"), Line::from("")];
+            match app.config.combined {
+                true => lines.extend(combined_lines(time, "-".into())),
+                false => lines.extend([Line::from(""), Line::from(time)]),
+            }
+            // shown early by `--penalty-warn-lead`, the penalty itself still follows `overtime`
+            let warning = (duration + app.config.penalty_warn_lead)
+                .checked_sub(INSPECT_DURATION)
+                .filter(|_| app.config.inspection_overtime)
+                .map(|over| match over <= INSPECT_OVERTIME_DNF {
+                    true => "+2",
+                    false => "DNF",
+                });
+            if let Some(warning) = warning {
+                lines.push(Line::from(Span::from(warning).bold()));
+            }
+            if let Some(line) = pb_target_line(app) {
+                lines.extend([Line::from(""), line]);
+            }
 
-            let (bg, fg) = if remaining < Duration::from_secs(3) {
-                (Color::Rgb(0xd0, 0x90, 0x60), Color::Rgb(0x90, 0x50, 0x30))
-            } else {
-                (Color::Rgb(0x70, 0x70, 0xd0), Color::Rgb(0x30, 0x30, 0x70))
+            let (fg, bg) = match warning {
+                Some(_) => (Color::Rgb(0xe0, 0x40, 0x40), Color::Rgb(0x90, 0x20, 0x20)),
+                None => inspect_colors(&app.config.warn_levels, remaining),
             };
-            centered_text(frame, lines, app.color_bg, bg, fg);
+            centered_text(frame, main, lines, app.color_bg, app.config.palette, fg, bg);
+            if app.config.inspection_gauge && !app.config.minimal {
+                let ratio = duration.as_secs_f64() / INSPECT_DURATION.as_secs_f64();
+                inspection_gauge(frame, main, ratio.min(1.0));
+            }
         }
         State::Solving(start) => {
-            let duration = Instant::now().duration_since(start);
-            let secs = duration.as_secs_f32();
-            let lines = vec![
-                Line::from("Solving"),
-                Line::from(""),
+            let time = format_time(app.solve_elapsed(start, now));
+            let mut lines = vec![
+                Line::from(match app.paused_at {
+                    Some(_) => "Paused",
+                    None => "Solving",
+                }),
                 Line::from(""),
-                Line::from(format!("{secs:.3}s")),
             ];
+            match (app.config.combined, app.inspection) {
+                (true, Some(inspection)) => {
+                    lines.extend(combined_lines(format_time(inspection), time));
+                }
+                _ => lines.extend([Line::from(""), Line::from(time)]),
+            }
             centered_text(
                 frame,
+                main,
                 lines,
                 app.color_bg,
+                app.config.palette,
                 Color::Rgb(0x50, 0xa0, 0x50),
                 Color::Rgb(0x30, 0x60, 0x30),
             );
+            if app.config.show_scramble_while_solving {
+                solving_scramble(app, frame, main);
+            }
         }
         State::Done(duration) => {
-            let secs = duration.as_secs_f32();
-            let lines = vec![
+            // with `--confirm-solved` the solve isn't in the history until it's answered
+            let current = app.unconfirmed.as_ref().or(app.history.last());
+            let time = match current {
+                Some(solve) => solve.to_string(),
+                None => format_time(duration),
+            };
+            let mut lines = vec![
                 Line::from("Done"),
+                Line::from(app.notice.unwrap_or_default()),
+                Line::from(""),
+            ];
+            match app.done_focus.average() {
+                Some(n) => {
+                    let average = match stats::average(app.session(), n) {
+                        Some(Some(time)) => format_time(time),
+                        Some(None) => "DNF".into(),
+                        None => "-".into(),
+                    };
+                    lines.push(Line::from(format!("ao{n} {average}")));
+                    lines.push(Line::from(Span::from(format!("single {time}")).dim()));
+                }
+                None => lines.push(Line::from(time)),
+            }
+            let mean3 = app.config.mean3 && !app.config.minimal;
+            if let (true, Some(mean)) = (mean3, stats::mo3(app.session())) {
+                let mean = mean.map_or_else(|| "DNF".into(), format_time);
+                lines.push(Line::from(Span::from(format!("mo3 {mean}")).dim()));
+            }
+            if current.is_some_and(|s| s.paused) {
+                lines.push(Line::from(Span::from("paused").dim()));
+            }
+            if let Some(memo) = current.and_then(|s| s.memo) {
+                lines.push(Line::from(format!("memo {}", format_time(memo))));
+            }
+            if let (true, Some(inspection)) = (
+                app.config.show_inspection,
+                current.and_then(|s| s.inspection),
+            ) {
+                let inspection = format!("inspection {}", format_time(inspection));
+                lines.push(Line::from(Span::from(inspection).dim()));
+            }
+            if !app.config.relay.is_empty() {
+                let puzzles: Vec<_> = app.config.relay.iter().map(|p| p.to_string()).collect();
+                lines.push(Line::from(Span::from(puzzles.join(" + ")).dim()));
+            }
+
+            let mut over_pace = false;
+            if !app.config.minimal {
+                // only meaningful if the displayed moves are what was executed
+                if let (ScrambleSource::Alg(alg), Some(solve)) = (&app.scrambles, current) {
+                    let num_moves = alg.moves().len();
+                    let tps = num_moves as f32 / solve.time.as_secs_f32();
+                    lines.push(Line::from(format!("{tps:.2} TPS ({num_moves} moves)")));
+                }
+
+                if let (Some(target), Some(solve)) = (app.config.pace_target, current) {
+                    over_pace = !stats::beats(solve, target);
+                    let session = app.session();
+                    let beaten = stats::counted(session)
+                        .filter(|s| stats::beats(s, target))
+                        .count();
+                    let streak = stats::streak(session, target);
+                    lines.push(Line::from(""));
+                    lines.push(Line::from(match over_pace {
+                        true => "over pace",
+                        false => "on pace",
+                    }));
+                    lines.push(Line::from(format!(
+                        "{beaten}/{} under {}, streak {streak}",
+                        stats::counted(session).count(),
+                        format_time(target),
+                    )));
+                }
+
+                // only right after the solve that reached it
+                if app.config.daily_goal == Some(history::count_today(&app.history)) {
+                    lines.push(Line::from(""));
+                    lines.push(Line::from("Daily goal reached, well done!"));
+                }
+            }
+
+            let (bg, fg) = if over_pace {
+                (Color::Rgb(0xc0, 0x50, 0x50), Color::Rgb(0x80, 0x30, 0x30))
+            } else {
+                (Color::Rgb(0xa0, 0x60, 0xa0), Color::Rgb(0x70, 0x30, 0x60))
+            };
+            centered_text(frame, main, lines, app.color_bg, app.config.palette, bg, fg);
+            if !app.config.minimal {
+                recent_solves(app, frame, main);
+            }
+        }
+        State::Summary { set, review } => {
+            let range = app.sets[set].clone();
+            let solves = &app.history[range];
+            let mut lines = vec![
+                Line::from(format!("Set {} of {}", set + 1, app.sets.len())),
                 Line::from(""),
                 Line::from(""),
-                Line::from(format!("{secs:.3}s")),
             ];
+            if review {
+                for (i, solve) in solves.iter().enumerate() {
+                    lines.push(solve_line(i, solves.len(), solve));
+                }
+            } else {
+                let fmt = |time: Option<Duration>| time.map_or_else(|| "-".into(), format_time);
+                let best = stats::counted(solves)
+                    .filter_map(SolveResult::final_time)
+                    .min();
+                let worst = stats::counted(solves)
+                    .filter_map(SolveResult::final_time)
+                    .max();
+                let count = stats::counted(solves).count();
+                lines.push(Line::from(format!("{count} solves")));
+                lines.push(Line::from(format!("mean {}", fmt(stats::mean(solves)))));
+                lines.push(Line::from(format!("best {}", fmt(best))));
+                lines.push(Line::from(format!("worst {}", fmt(worst))));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(""));
+            lines.push(Line::from(
+                "space: new set  l: review  ←/→: other sets  q: quit",
+            ));
+
             centered_text(
                 frame,
+                main,
                 lines,
                 app.color_bg,
+                app.config.palette,
                 Color::Rgb(0xa0, 0x60, 0xa0),
                 Color::Rgb(0x70, 0x30, 0x60),
             );
         }
     }
+
+    // nothing but the timer while solving, the toggles stay and apply again once done
+    let solving = matches!(app.state, State::Solving(_));
+    match side {
+        Some(side) => {
+            app.refresh_histogram();
+            stats_overlay(app, frame, side);
+        }
+        None if app.show_stats && !app.config.minimal && !solving => {
+            app.refresh_histogram();
+            stats_overlay(app, frame, frame.size());
+        }
+        None => (),
+    }
+    if app.show_scramble_stats && !app.config.minimal && !solving {
+        scramble_stats_overlay(&app.scramble_stats, app.config.ascii, frame);
+    }
+    if app.show_list && !app.config.minimal && !solving {
+        list_overlay(app, frame);
+    }
+    if let Some(editor) = &app.reconstruction {
+        let area = centered_rect(60, 16, frame.size());
+        let block =
+            overlay_block(app.config.ascii).title(" Reconstruction (ctrl-s: save, esc: cancel) ");
+        let p = Paragraph::new(format!("{}_", editor.text))
+            .block(block)
+            .wrap(Wrap { trim: false });
+        frame.render_widget(Clear, area);
+        frame.render_widget(p, area);
+    }
+    if let Some(confirm) = app.confirm {
+        let prompt = confirm.prompt();
+        let area = centered_rect(prompt.len() as u16 + 6, 3, frame.size());
+        let p = Paragraph::new(prompt)
+            .block(overlay_block(app.config.ascii))
+            .alignment(Alignment::Center);
+        frame.render_widget(Clear, area);
+        frame.render_widget(p, area);
+    }
+
+    let quiet = matches!(app.state, State::Inspecting(_) | State::Solving(_));
+    if !app.config.minimal && !quiet {
+        footer(app, frame, main);
+    }
+
+    if app.show_clock && !solving {
+        let now = chrono::Local::now().format("%H:%M");
+        let p = Paragraph::new(format!("{now} ")).alignment(Alignment::Right);
+        frame.render_widget(p, frame.size());
+    }
+
+    if app.unsaved && !solving {
+        let p = Paragraph::new(Span::from(" history not saved").red());
+        frame.render_widget(p, frame.size());
+    }
+
+    if let Some(since) = app.flash_since {
+        if now.duration_since(since) < FLASH_DURATION {
+            let area = frame.size();
+            frame.buffer_mut().set_style(area, Style::new().reversed());
+        } else {
+            app.flash_since = None;
+        }
+    }
+}
+
+fn stats_overlay(app: &App, frame: &mut Frame, area: Rect) {
+    let session = app.session();
+    let count = stats::counted(session).count();
+    let fmt = |time: Option<Duration>| match time {
+        Some(time) => format_time_aligned(time, TIME_WIDTH),
+        None => format!("{:>TIME_WIDTH$}", "-"),
+    };
+    let mut summary = vec![
+        Line::from(format!("solves: {count}")),
+        Line::from(format!("mean:   {}", fmt(stats::mean(session)))),
+    ];
+    if app.config.mean_without_worst {
+        summary.push(Line::from(format!(
+            "mean-1: {} (unofficial)",
+            fmt(stats::mean_without_worst(session))
+        )));
+    }
+    summary.extend([
+        Line::from(format!("stddev: {}", fmt(stats::stddev(session)))),
+        Line::from(format!(
+            "form:   {}",
+            fmt(stats::ewma(session, app.config.form_alpha))
+        )),
+    ]);
+    for n in [5, 12] {
+        let average = match stats::average(session, n) {
+            Some(Some(time)) => format_time_aligned(time, TIME_WIDTH),
+            Some(None) => format!("{:>TIME_WIDTH$}", "DNF"),
+            None if app.config.show_solves_needed => {
+                format!("{} more solves", n - count)
+            }
+            None => continue,
+        };
+        let label = format!("ao{n}:");
+        summary.push(Line::from(format!("{label:<8}{average}")));
+    }
+    if let Some((first, second)) = stats::improvement(session) {
+        let delta = first.as_secs_f32() - second.as_secs_f32();
+        summary.push(Line::from(match delta >= 0.0 {
+            true => format!("improved by {delta:.1}s"),
+            false => format!("slowed by {:.1}s", -delta),
+        }));
+    }
+    let intervals = stats::solve_intervals(session);
+    if let Some((median, longest, breaks)) = stats::gaps(&intervals, BREAK_AFTER) {
+        let secs = |time: Duration| format!("{:.0}s", time.as_secs_f32());
+        summary.push(Line::from(format!(
+            "gaps:   {}, max {}",
+            secs(median),
+            secs(longest)
+        )));
+        if breaks > 0 {
+            summary.push(Line::from(format!("breaks: {breaks}")));
+        }
+    }
+    if app.config.mean3 {
+        let mean = match stats::mo3(session) {
+            Some(Some(time)) => format_time_aligned(time, TIME_WIDTH),
+            Some(None) => format!("{:>TIME_WIDTH$}", "DNF"),
+            None => format!("{:>TIME_WIDTH$}", "-"),
+        };
+        summary.push(Line::from(format!("mo3:    {mean}")));
+    }
+
+    let bucket = app.config.histogram_bucket;
+    let histogram = app.histogram.as_ref().map_or(&[][..], |(_, h)| h);
+    let bars: Vec<_> = histogram
+        .iter()
+        .map(|&(start, count)| {
+            let label = if start == Duration::MAX {
+                "DNF".to_string()
+            } else {
+                format!("{:.1}s", start.as_secs_f32())
+            };
+            Bar::default().value(count as u64).label(Line::from(label))
+        })
+        .collect();
+
+    let graph: Vec<_> = app.graph.iter().copied().collect();
+    let graph_height = match app.graph.is_empty() {
+        true => 0,
+        false => GRAPH_HEIGHT + 1,
+    };
+
+    let height = summary.len() as u16 + graph_height + bars.len() as u16 + 3;
+    let area = centered_rect(40, height, area);
+    let block = overlay_block(app.config.ascii)
+        .title(format!(" Stats (bucket {:.1}s) ", bucket.as_secs_f32()))
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let [summary_area, graph_area, histogram_area] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(summary.len() as u16 + 1),
+            Constraint::Length(graph_height),
+            Constraint::Min(0),
+        ])
+        .areas(inner);
+    frame.render_widget(Paragraph::new(summary), summary_area);
+    let graph_area = Rect {
+        height: graph_area.height.saturating_sub(1),
+        ..graph_area
+    };
+    let bar_set = match app.config.ascii {
+        true => ASCII_BARS,
+        false => symbols::bar::NINE_LEVELS,
+    };
+    let sparkline = Sparkline::default().data(&graph).bar_set(bar_set.clone());
+    frame.render_widget(sparkline, graph_area);
+
+    let chart = BarChart::default()
+        .bar_set(bar_set)
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .data(BarGroup::default().bars(&bars));
+    frame.render_widget(chart, histogram_area);
 }
 
+/// The scramble of the solve in progress, in the upper half so the time stays in place.
+fn solving_scramble(app: &App, frame: &mut Frame, size: Rect) {
+    let mut lines = Vec::new();
+    match (&app.scrambles, app.solving_scramble) {
+        (
+            ScrambleSource::Relay {
+                puzzles, scrambles, ..
+            },
+            _,
+        ) => {
+            for (puzzle, scramble) in puzzles.iter().zip(scrambles) {
+                lines.push(Line::from(Span::from(puzzle.to_string()).dim()));
+                scramble_lines(&mut lines, scramble, &app.config, 0, None);
+            }
+        }
+        (_, Some(scramble)) => scramble_lines(&mut lines, &scramble, &app.config, 0, None),
+        _ => return,
+    }
+
+    let height = (size.height / 2).saturating_sub(2);
+    let area = Rect {
+        y: size.y + height.saturating_sub(lines.len() as u16),
+        height: height.min(lines.len() as u16),
+        ..size
+    };
+    let p = Paragraph::new(lines).alignment(Alignment::Center);
+    frame.render_widget(p, area);
+}
+
+/// A dim line at the bottom of the screen.
+fn footer(app: &App, frame: &mut Frame, size: Rect) {
+    let mut parts = Vec::new();
+    if let Some(name) = &app.config.session_name {
+        parts.push(name.clone());
+    }
+    if let Some(target) = app.config.streak {
+        parts.push(format!(
+            "streak: {} sub-{}, best {}",
+            app.streak,
+            target.as_secs_f32(),
+            app.best_streak,
+        ));
+    }
+    // warmup solves are attempts too, so they are counted in both
+    if app.attempts > 0 {
+        let solves = app.session().len();
+        parts.push(format!("solves: {solves}, attempts: {}", app.attempts));
+    }
+    if app.read_only_warning {
+        parts.push("history not being saved: read-only".into());
+    }
+    if parts.is_empty() {
+        return;
+    }
+
+    let area = Rect {
+        y: size.bottom().saturating_sub(1),
+        height: size.height.min(1),
+        ..size
+    };
+    let mut style = Style::new();
+    if app.color_bg {
+        style = style.fg(Color::Rgb(0xe0, 0xe0, 0xc0));
+    }
+    let p = Paragraph::new(Span::from(parts.join("  ")).dim())
+        .style(style)
+        .alignment(Alignment::Center);
+    frame.render_widget(p, area);
+}
+
+/// The last few session solves in a column on the right of `area`.
+fn recent_solves(app: &App, frame: &mut Frame, area: Rect) {
+    let session = app.session();
+    let start = session.len().saturating_sub(RECENT_SOLVES);
+    let mut lines = vec![Line::from(Span::from("last solves").dim())];
+    for (i, solve) in session.iter().enumerate().skip(start).rev() {
+        lines.push(solve_line(i, session.len(), solve));
+    }
+
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 2;
+    let [_, column] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(width)])
+        .areas(area);
+    let height = lines.len() as u16;
+    let area = Rect {
+        y: column.y + column.height.saturating_sub(height) / 2,
+        height: height.min(column.height),
+        ..column
+    };
+
+    let mut style = Style::new();
+    if app.color_bg {
+        style = style.fg(Color::Rgb(0xe0, 0xe0, 0xc0));
+    }
+    let p = Paragraph::new(lines)
+        .style(style)
+        .alignment(Alignment::Right)
+        .block(Block::new().padding(Padding::right(1)));
+    frame.render_widget(p, area);
+}
+
+/// A numbered solve, greyed out if it's a warmup. Padded to line up with other solves up to
+/// number `count`.
+fn solve_line(i: usize, count: usize, solve: &SolveResult) -> Line<'static> {
+    let num_width = count.to_string().len();
+    let line = Line::from(format!("{:>num_width$}. {solve:TIME_WIDTH$}", i + 1));
+    match solve.warmup {
+        true => line.style(Style::new().dark_gray()),
+        false => line,
+    }
+}
+
+/// The area for the timer and scramble, and with `--wide-layout` on a wide enough terminal a
+/// column on the right for the stats. Not while inspecting or solving, which keep the whole
+/// screen.
+fn columns(app: &App, size: Rect) -> (Rect, Option<Rect>) {
+    let quiet = matches!(app.state, State::Inspecting(_) | State::Solving(_));
+    match app.config.wide_layout {
+        Some(width) if size.width >= width && !quiet && !app.config.minimal => {
+            let [main, side] = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(0), Constraint::Length(STATS_COLUMN)])
+                .areas(size);
+            (main, Some(side))
+        }
+        _ => (size, None),
+    }
+}
+
+/// Session solves with their reconstructions, the most recent ones that fit.
+fn list_overlay(app: &App, frame: &mut Frame) {
+    let area = centered_rect(60, frame.size().height.saturating_sub(4), frame.size());
+    let block = overlay_block(app.config.ascii)
+        .title(" Solves ")
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(area);
+
+    let mut lines = Vec::new();
+    for (i, solve) in app.session().iter().enumerate().rev() {
+        let mut entry = vec![solve_line(i, app.session().len(), solve)];
+        if let (true, Some(inspection)) = (app.config.show_inspection, solve.inspection) {
+            let inspection = format!("  insp {}", format_time(inspection));
+            entry[0].spans.push(Span::from(inspection).dim());
+        }
+        if let Some(reconstruction) = &solve.reconstruction {
+            for l in reconstruction.lines() {
+                entry.push(Line::from(Span::from(format!("   {l}")).dim()));
+            }
+        }
+        if lines.len() + entry.len() > inner.height as usize {
+            break;
+        }
+        lines.splice(0..0, entry);
+    }
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// How often each face and modifier was part of the scrambles this session.
+fn scramble_stats_overlay(stats: &ScrambleStats, ascii: bool, frame: &mut Frame) {
+    let moves = stats.moves();
+    let share = |count: usize| 100.0 * count as f32 / moves.max(1) as f32;
+    let mut lines = vec![
+        Line::from(format!("scrambles {:>9}", stats.scrambles)),
+        Line::from(format!("moves     {moves:>9}")),
+        Line::from(""),
+    ];
+    for (face, &count) in ScrambleStats::FACES.iter().zip(&stats.faces) {
+        lines.push(Line::from(format!(
+            "{face:<6}{count:>6}{:>7.1}%",
+            share(count)
+        )));
+    }
+    lines.push(Line::from(""));
+    for (modifier, &count) in ["plain", "'", "2"].iter().zip(&stats.modifiers) {
+        lines.push(Line::from(format!(
+            "{modifier:<6}{count:>6}{:>7.1}%",
+            share(count)
+        )));
+    }
+
+    let area = centered_rect(24, lines.len() as u16 + 2, frame.size());
+    let block = overlay_block(ascii)
+        .title(" Scramble stats ")
+        .padding(Padding::horizontal(1));
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// The inspection and solve time side by side below their labels, for `--combined`.
+fn combined_lines(inspection: String, solve: String) -> [Line<'static>; 2] {
+    let labels = format!("{:>10}   {:<10}", "inspection", "solve");
+    let times = format!("{inspection:>10}   {solve:<10}");
+    [Line::from(Span::from(labels).dim()), Line::from(times)]
+}
+
+/// A bar below the inspection time, turning from green to red as it fills up.
+fn inspection_gauge(frame: &mut Frame, size: Rect, ratio: f64) {
+    // the time is on the 4th line of the text centered by `centered_text`
+    let [_, row, _] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length((size.height / 2).saturating_sub(1) + 5),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .areas(size);
+    let area = centered_rect(40, 1, row);
+
+    let color = lerp_color(
+        Color::Rgb(0x50, 0xc0, 0x50),
+        Color::Rgb(0xd0, 0x40, 0x40),
+        ratio as f32,
+    );
+    let gauge = Gauge::default()
+        .gauge_style(Style::new().fg(color).bg(Color::Rgb(0x30, 0x30, 0x30)))
+        .ratio(ratio)
+        .label("")
+        .use_unicode(true);
+    frame.render_widget(gauge, area);
+}
+
+/// The colors of the lowest warn level that was reached, fading towards the next one.
+fn inspect_colors(levels: &[WarnLevel], remaining: Duration) -> (Color, Color) {
+    let mut colors = (Color::Rgb(0x70, 0x70, 0xd0), Color::Rgb(0x30, 0x30, 0x70));
+    for level in levels {
+        if remaining <= level.remaining {
+            colors = (level.fg, level.bg);
+            continue;
+        }
+
+        let until = remaining - level.remaining;
+        let t = 1.0 - until.as_secs_f32() / INSPECT_WARN_FADE.as_secs_f32();
+        return (
+            lerp_color(colors.0, level.fg, t),
+            lerp_color(colors.1, level.bg, t),
+        );
+    }
+    colors
+}
+
+/// Linearly interpolates between two rgb colors, `t` is clamped to `0.0..=1.0`. Other colors
+/// can't be blended and switch at the midpoint.
+fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    match (a, b) {
+        (Color::Rgb(ar, ag, ab), Color::Rgb(br, bg, bb)) => {
+            let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+            Color::Rgb(lerp(ar, br), lerp(ag, bg), lerp(ab, bb))
+        }
+        _ if t < 0.5 => a,
+        _ => b,
+    }
+}
+
+/// A rect of at most `width` x `height` centered in `area`.
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// The stickers of a scrambled cube unfolded, with `U` on top and `L F R B` in the middle. A
+/// 2x2 is shown as the corners of the 3x3.
+fn net_lines(state: &CubeState, puzzle: Puzzle, ascii: bool) -> Vec<Line<'static>> {
+    let size = match puzzle {
+        Puzzle::TwoByTwo => 2,
+        Puzzle::ThreeByThree => 3,
+    };
+    let facelet = |row: usize, col: usize| match puzzle {
+        Puzzle::TwoByTwo => row * 6 + col * 2,
+        Puzzle::ThreeByThree => row * 3 + col,
+    };
+    // faces in the order of `CubeState::faces`
+    let letters = ["G ", "B ", "O ", "R ", "W ", "Y "];
+    let colors = [
+        Color::Rgb(0x00, 0x9b, 0x48),
+        Color::Rgb(0x00, 0x46, 0xad),
+        Color::Rgb(0xff, 0x58, 0x00),
+        Color::Rgb(0xb7, 0x12, 0x34),
+        Color::Rgb(0xff, 0xff, 0xff),
+        Color::Rgb(0xff, 0xd5, 0x00),
+    ];
+    let [f, b, l, r, u, d] = [0, 1, 2, 3, 4, 5];
+    let rows: [[Option<usize>; 4]; 3] = [
+        [None, Some(u), None, None],
+        [Some(l), Some(f), Some(r), Some(b)],
+        [None, Some(d), None, None],
+    ];
+
+    let faces = state.faces();
+    let mut lines = Vec::new();
+    for row in rows {
+        for y in 0..size {
+            let mut spans = Vec::new();
+            for face in row {
+                for x in 0..size {
+                    spans.push(match face {
+                        Some(face) => {
+                            let color = faces[face][facelet(y, x)] as usize;
+                            match ascii {
+                                true => Span::styled(letters[color], colors[color]),
+                                false => Span::styled("  ", Style::new().bg(colors[color])),
+                            }
+                        }
+                        None => Span::from("  "),
+                    });
+                }
+                spans.push(Span::from(" "));
+            }
+            spans.pop();
+            lines.push(Line::from(spans));
+        }
+    }
+    lines
+}
+
+/// Pushes the moves split over two lines, or lines of `--scramble-columns` moves, each followed
+/// by an empty line. With a `step`, that move is highlighted and the ones after it dimmed.
+fn scramble_lines<'a>(
+    lines: &mut Vec<Line<'a>>,
+    scramble: &Scramble,
+    config: &'a Config,
+    color_shift: u8,
+    step: Option<usize>,
+) {
+    let moves = scramble.moves();
+    if moves.is_empty() {
+        lines.push(Line::from(Span::from("(no moves)").dim()));
+        lines.push(Line::from(""));
+        return;
+    }
+    let num_line_moves = match config.scramble_columns {
+        Some(columns) => columns,
+        None => moves.len().div_ceil(2).max(1),
+    };
+    for (line, moves) in moves.chunks(num_line_moves).enumerate() {
+        let style = |i: usize| match step.map(|step| (line * num_line_moves + i).cmp(&step)) {
+            None | Some(Ordering::Less) => Style::new(),
+            Some(Ordering::Equal) => Style::new().bold().underlined(),
+            Some(Ordering::Greater) => Style::new().dim(),
+        };
+        let spans = scramble_spans(moves, &config.notation, color_shift, style);
+        lines.push(Line::from(spans));
+        lines.push(Line::from(""));
+    }
+}
+
+/// The moves colored by face, with the style of the move at each index added. `color_shift`
+/// rotates the colors through the faces.
+fn scramble_spans<'a>(
+    moves: &[impl ScrambleMove],
+    notation: &'a Notation,
+    color_shift: u8,
+    style: impl Fn(usize) -> Style,
+) -> Vec<Span<'a>> {
+    let mut spans = Vec::with_capacity(2 * moves.len());
+    for (i, mov) in moves.iter().enumerate() {
+        let mut str = String::with_capacity(4);
+        if notation.compact {
+            write!(&mut str, "{mov:#}").ok();
+        } else {
+            write!(&mut str, "{mov}").ok();
+        }
+        let color = Color::Indexed((mov.face_index() + color_shift) % 6 + 1);
+        let style = Style::new().fg(color).patch(style(i));
+        let modifier_style = match mov.modifier_char() {
+            Some('2') => notation.double_style,
+            Some('\'') => notation.reverse_style,
+            _ => Modifier::empty(),
+        };
+        // the modifier is a separate span only when styled, to keep the plain case simple
+        if modifier_style.is_empty() {
+            spans.push(Span::styled(str, style));
+        } else {
+            let modifier = str.split_off(1);
+            spans.push(Span::styled(str, style));
+            spans.push(Span::styled(modifier, style.add_modifier(modifier_style)));
+        }
+        spans.push(Span::from(notation.separator.as_str()));
+    }
+    spans.pop();
+    spans
+}
+
+/// A bordered block for an overlay.
+fn overlay_block<'a>(ascii: bool) -> Block<'a> {
+    let border_set = match ascii {
+        true => ASCII_BORDER,
+        false => symbols::border::PLAIN,
+    };
+    Block::new().borders(Borders::ALL).border_set(border_set)
+}
+
+/// Centers `text` in `area`, with the background of the whole frame colored if `color_bg`.
 fn centered_text<'a>(
     frame: &mut Frame,
+    area: Rect,
     text: impl Into<Text<'a>>,
     color_bg: bool,
+    palette: Palette,
     fg_color: Color,
     bg_color: Color,
 ) {
-    let size = frame.size();
-    let mut block = Block::new().padding(Padding::top((size.height / 2).saturating_sub(1)));
+    let white = Color::Rgb(0xff, 0xff, 0xff);
+    let black = Color::Rgb(0x00, 0x00, 0x00);
+    let (fg_color, bg_color, fg_on_bg) = match palette {
+        Palette::Default => (fg_color, bg_color, Color::Rgb(0xe0, 0xe0, 0xc0)),
+        Palette::HighContrast => (
+            lerp_color(fg_color, white, 0.5),
+            lerp_color(bg_color, black, 0.3),
+            white,
+        ),
+        Palette::Subtle => (
+            lerp_color(fg_color, Color::Rgb(0x50, 0x50, 0x50), 0.5),
+            lerp_color(bg_color, Color::Rgb(0x10, 0x10, 0x10), 0.6),
+            Color::Rgb(0x90, 0x90, 0x80),
+        ),
+    };
+
     if color_bg {
-        block = block.style(Style::new().bg(bg_color));
+        frame.render_widget(Block::new().style(Style::new().bg(bg_color)), frame.size());
     }
+    let block = Block::new().padding(Padding::top((area.height / 2).saturating_sub(1)));
 
-    let fg_color = if color_bg {
-        Color::Rgb(0xe0, 0xe0, 0xc0)
-    } else {
-        fg_color
-    };
+    let fg_color = if color_bg { fg_on_bg } else { fg_color };
+    let mut style = Style::new().fg(fg_color);
+    if palette != Palette::Subtle {
+        style = style.bold();
+    }
 
     let p = Paragraph::new(text)
         .block(block)
-        .style(Style::new().fg(fg_color).bold())
+        .style(style)
         .alignment(Alignment::Center);
-    frame.render_widget(p, size);
+    frame.render_widget(p, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_solve_is_recorded_once() {
+        let mut app = App {
+            read_only: true,
+            ..Default::default()
+        };
+
+        app.next();
+        assert!(matches!(app.state, State::Inspecting(_)));
+        app.next();
+        assert!(matches!(app.state, State::Solving(_)));
+        app.state = State::Solving(Instant::now() - Duration::from_secs(10));
+        app.next();
+        assert!(matches!(app.state, State::Done(_)));
+        assert_eq!(app.history.len(), 1);
+
+        for _ in 0..10 {
+            update(&mut app);
+        }
+        assert_eq!(app.history.len(), 1);
+        app.next();
+        assert!(app.state.is_idle());
+        assert_eq!(app.history.len(), 1);
+        assert_eq!(app.session().len(), 1);
+    }
+
+    #[test]
+    fn paused_time_is_not_counted() {
+        let mut app = App {
+            read_only: true,
+            state: State::Solving(Instant::now() - Duration::from_secs(10)),
+            ..Default::default()
+        };
+        app.toggle_pause();
+        app.paused_at = app.paused_at.map(|at| at - Duration::from_secs(5));
+        // stopped while paused
+        app.next();
+        let solve = app.history.last().unwrap();
+        assert_eq!(solve.time.as_secs(), 5);
+        assert!(solve.paused);
+
+        app.next();
+        app.next();
+        app.state = State::Solving(Instant::now() - Duration::from_secs(10));
+        app.toggle_pause();
+        app.paused_at = app.paused_at.map(|at| at - Duration::from_secs(3));
+        app.toggle_pause();
+        app.next();
+        assert_eq!(app.history.last().map(|s| s.time.as_secs()), Some(7));
+    }
+
+    #[test]
+    fn go_skips_to_inspection() {
+        let mut app = App {
+            read_only: true,
+            ..Default::default()
+        };
+        let State::Idle(first) = app.state else {
+            panic!("not idle");
+        };
+        app.go();
+        assert!(matches!(app.state, State::Inspecting(_)));
+        assert_ne!(app.solving_scramble, Some(first));
+        assert_eq!(app.scramble_index, 1);
+        assert_eq!(app.scrambles.draws(), Some(1));
+
+        app.state = State::Solving(Instant::now() - Duration::from_secs(10));
+        app.next();
+        app.go();
+        assert!(matches!(app.state, State::Inspecting(_)));
+        assert_eq!(app.history.len(), 1);
+        assert_eq!(app.scramble_index, 2);
+        assert_eq!(app.scrambles.draws(), Some(2));
+    }
+
+    #[test]
+    fn solves_keep_their_scramble() {
+        let mut app = App {
+            read_only: true,
+            ..Default::default()
+        };
+        let State::Idle(scramble) = app.state else {
+            panic!("not idle");
+        };
+        app.next();
+        app.state = State::Solving(Instant::now() - Duration::from_secs(10));
+        app.next();
+        let solve = app.history.last().unwrap();
+        assert_eq!(solve.scramble, Some(format!("{scramble:#}")));
+    }
+
+    #[test]
+    fn cancelling_a_finished_set_shows_the_summary() {
+        let mut app = App {
+            read_only: true,
+            config: Config {
+                target: Some(1),
+                ..Default::default()
+            },
+            state: State::Solving(Instant::now() - Duration::from_secs(10)),
+            ..Default::default()
+        };
+        app.next();
+        assert!(matches!(app.state, State::Done(_)));
+        app.cancel();
+        assert!(matches!(app.state, State::Summary { set: 0, .. }));
+        app.cancel();
+        assert!(matches!(app.state, State::Summary { .. }));
+        assert_eq!(app.attempts, 1);
+    }
+
+    #[test]
+    fn skipped_scrambles_are_attempts() {
+        let mut app = App {
+            read_only: true,
+            ..Default::default()
+        };
+        let State::Idle(first) = app.state else {
+            panic!("not idle");
+        };
+        see_scramble(&mut app, first);
+        let index = app.scramble_index;
+        app.rescramble();
+        assert_eq!(app.attempts, 1);
+        let State::Idle(second) = app.state else {
+            panic!("not idle");
+        };
+        see_scramble(&mut app, second);
+        assert_eq!(app.scramble_index, app.scrambles.draws().unwrap());
+        app.undo_rescramble();
+        assert_eq!(app.state, State::Idle(first));
+        assert_eq!(app.attempts, 0);
+        assert_eq!(app.scramble_index, index);
+        app.undo_rescramble();
+        assert_eq!(app.attempts, 0);
+
+        app.go();
+        assert_eq!(app.attempts, 1);
+        app.state = State::Solving(Instant::now() - Duration::from_secs(10));
+        app.next();
+        assert_eq!(app.attempts, 2);
+    }
+
+    #[test]
+    fn read_only_skips_saving() {
+        let mut app = App {
+            read_only: true,
+            state: State::Solving(Instant::now() - Duration::from_secs(10)),
+            ..Default::default()
+        };
+        app.next();
+        assert_eq!(app.history.len(), 1);
+        assert_eq!(app.last_save, None);
+        assert!(!app.unsaved);
+    }
+
+    #[test]
+    fn histogram_is_recounted_on_change() {
+        let mut app = App {
+            read_only: true,
+            ..Default::default()
+        };
+        app.refresh_histogram();
+        assert_eq!(
+            app.histogram,
+            Some((app.config.histogram_bucket, Vec::new()))
+        );
+
+        app.record(SolveResult::new(Duration::from_millis(10_500)));
+        assert_eq!(app.histogram, None);
+        app.refresh_histogram();
+        let second = Duration::from_secs(1);
+        assert_eq!(app.histogram, Some((second, vec![(second * 10, 1)])));
+
+        app.config.histogram_bucket = second * 2;
+        app.refresh_histogram();
+        assert_eq!(app.histogram, Some((second * 2, vec![(second * 10, 1)])));
+    }
+
+    #[test]
+    fn empty_scramble_renders() {
+        let mut app = App::default();
+        app.config.net = true;
+        app.config.scramble_rotation = true;
+        app.state = State::Idle(Scramble::empty());
+        update(&mut app);
+
+        let backend = ratatui::backend::TestBackend::new(60, 30);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|f| ui(&mut app, f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(text.contains("(no moves)"));
+    }
+
+    #[test]
+    fn done_shows_the_unconfirmed_solve() {
+        let mut app = App {
+            read_only: true,
+            ..Default::default()
+        };
+        app.config.confirm_solved = true;
+        app.config.pace_target = Some(Duration::from_secs(20));
+        app.record(SolveResult::new(Duration::from_secs(30)));
+        app.state = State::Solving(Instant::now() - Duration::from_secs(10));
+        app.toggle_pause();
+        app.next();
+        assert!(app.unconfirmed.is_some());
+        assert!(matches!(app.state, State::Done(_)));
+
+        let backend = ratatui::backend::TestBackend::new(60, 30);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|f| ui(&mut app, f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(text.contains("10.000s"));
+        assert!(text.contains("paused"));
+        assert!(text.contains("on pace"));
+        assert!(!text.contains("over pace"));
+    }
+
+    #[test]
+    fn minimal_done_hides_the_mo3() {
+        let mut app = App {
+            read_only: true,
+            ..Default::default()
+        };
+        app.config.mean3 = true;
+        for secs in [10, 11, 12] {
+            app.record(SolveResult::new(Duration::from_secs(secs)));
+        }
+        app.state = State::Done(Duration::from_secs(12));
+
+        let render = |app: &mut App| {
+            let backend = ratatui::backend::TestBackend::new(60, 30);
+            let mut terminal = ratatui::Terminal::new(backend).unwrap();
+            terminal.draw(|f| ui(app, f)).unwrap();
+            let buffer = terminal.backend().buffer();
+            buffer
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect::<String>()
+        };
+        assert!(render(&mut app).contains("mo3 11.000s"));
+        app.config.minimal = true;
+        assert!(!render(&mut app).contains("mo3"));
+    }
+
+    #[test]
+    fn minimal_hides_the_pb_target() {
+        let mut app = App {
+            read_only: true,
+            ..Default::default()
+        };
+        app.config.pb_target = true;
+        for secs in [12, 10, 11, 13, 14] {
+            app.record(SolveResult::new(Duration::from_secs(secs)));
+        }
+        assert!(pb_target_line(&app).is_some());
+        app.config.minimal = true;
+        assert!(pb_target_line(&app).is_none());
+    }
+
+    #[test]
+    fn read_only_warning_in_the_footer() {
+        let mut app = App {
+            read_only: true,
+            read_only_warning: true,
+            ..Default::default()
+        };
+        let render = |app: &mut App| {
+            let backend = ratatui::backend::TestBackend::new(60, 30);
+            let mut terminal = ratatui::Terminal::new(backend).unwrap();
+            terminal.draw(|f| ui(app, f)).unwrap();
+            let buffer = terminal.backend().buffer();
+            let last_row = (29 * 60) as usize..;
+            buffer.content()[last_row]
+                .iter()
+                .map(|c| c.symbol())
+                .collect::<String>()
+        };
+        assert!(render(&mut app).contains("history not being saved: read-only"));
+        app.read_only_warning = false;
+        assert!(!render(&mut app).contains("read-only"));
+    }
+
+    #[test]
+    fn tiny_terminal_shows_the_state_glyph() {
+        let mut app = App {
+            state: State::Solving(Instant::now()),
+            ..Default::default()
+        };
+        let backend = ratatui::backend::TestBackend::new(10, 2);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|f| ui(&mut app, f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer.get(0, 0).symbol(), "S");
+    }
+
+    #[test]
+    fn unconfirmed_solve_is_not_recorded() {
+        let mut app = App {
+            read_only: true,
+            ..Default::default()
+        };
+        app.config.confirm_solved = true;
+        for solved in [true, false] {
+            app.state = State::Solving(Instant::now() - Duration::from_secs(10));
+            app.next();
+            assert!(app.unconfirmed.is_some());
+            assert_eq!(app.confirm, Some(Confirm::Solved));
+            app.confirm_solved(solved);
+            assert_eq!(app.confirm, None);
+            let penalty = app.history.last().map(|s| s.penalty);
+            assert_eq!(
+                penalty,
+                Some(if solved { Penalty::None } else { Penalty::Dnf })
+            );
+        }
+        assert_eq!(app.history.len(), 2);
+    }
+
+    #[test]
+    fn inspection_overtime_penalty() {
+        let mut app = App {
+            read_only: true,
+            ..Default::default()
+        };
+        app.config.inspection_overtime = true;
+        for (inspected, penalty) in [
+            (10, Penalty::None),
+            (16, Penalty::Plus2),
+            (18, Penalty::Dnf),
+        ] {
+            app.state = State::Inspecting(Instant::now() - Duration::from_secs(inspected));
+            update(&mut app);
+            assert!(matches!(app.state, State::Inspecting(_)));
+            app.next();
+            app.state = State::Solving(Instant::now() - Duration::from_secs(10));
+            app.next();
+            assert_eq!(app.history.last().map(|s| s.penalty), Some(penalty));
+            let used = app.history.last().and_then(|s| s.inspection);
+            assert!(used.is_some_and(|t| t.as_secs() == inspected));
+        }
+    }
+
+    #[test]
+    fn oneshot_stats_format() {
+        assert_eq!(oneshot_stats(&[]), "best - | ao5 - | n 0");
+
+        let mut solves: Vec<_> = [9870, 12000, 11000, 11500, 11200]
+            .map(|ms| SolveResult::new(Duration::from_millis(ms)))
+            .into();
+        assert_eq!(oneshot_stats(&solves), "best 9.87 | ao5 11.23 | n 5");
+        solves[0].penalty = Penalty::Dnf;
+        solves[1].penalty = Penalty::Dnf;
+        assert_eq!(oneshot_stats(&solves), "best 11.00 | ao5 DNF | n 5");
+    }
+
+    fn text(spans: &[Span]) -> String {
+        spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    /// The colored idle screen scramble must read the same as the plain one.
+    #[test]
+    fn scramble_spans_match_display() {
+        for _ in 0..50 {
+            let scramble = Scramble::random();
+            let mut notation = Notation::default();
+            let padded = format!("{scramble}");
+            assert_eq!(
+                text(&scramble_spans(scramble.moves(), &notation, 0, |_| {
+                    Style::new()
+                })),
+                padded
+            );
+
+            notation.compact = true;
+            let compact = format!("{scramble:#}");
+            assert_eq!(
+                text(&scramble_spans(scramble.moves(), &notation, 3, |_| {
+                    Style::new()
+                })),
+                compact
+            );
+
+            notation.separator = ", ".into();
+            assert_eq!(
+                text(&scramble_spans(scramble.moves(), &notation, 0, |_| {
+                    Style::new()
+                })),
+                compact.replace(' ', ", ")
+            );
+
+            notation.double_style = Modifier::BOLD;
+            notation.reverse_style = Modifier::DIM;
+            let spans = scramble_spans(scramble.moves(), &notation, 0, |_| Style::new());
+            assert_eq!(text(&spans), compact.replace(' ', ", "));
+            for span in spans {
+                let bold = span.style.add_modifier.contains(Modifier::BOLD);
+                assert_eq!(bold, span.content == "2");
+            }
+
+            for columns in [None, Some(7)] {
+                let config = Config {
+                    scramble_columns: columns,
+                    ..Default::default()
+                };
+                let mut lines = Vec::new();
+                scramble_lines(&mut lines, &scramble, &config, 0, None);
+                let rows: Vec<_> = lines
+                    .iter()
+                    .map(|l| text(&l.spans))
+                    .filter(|t| !t.is_empty())
+                    .collect();
+                assert_eq!(rows.join(" "), padded);
+            }
+        }
+    }
 }
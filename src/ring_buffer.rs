@@ -0,0 +1,69 @@
+/// Keeps the last `capacity` pushed items, dropping the oldest.
+#[derive(Clone, Debug, Default)]
+pub struct RingBuffer<T> {
+    items: Vec<T>,
+    capacity: usize,
+    /// Index of the oldest item once full.
+    start: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            items: Vec::with_capacity(capacity),
+            capacity,
+            start: 0,
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+        } else if self.capacity > 0 {
+            self.items[self.start] = item;
+            self.start = (self.start + 1) % self.capacity;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.start = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let (newer, older) = self.items.split_at(self.start);
+        older.iter().chain(newer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_last_items() {
+        let mut buf = RingBuffer::new(3);
+        assert!(buf.is_empty());
+        for i in 0..2 {
+            buf.push(i);
+        }
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [0, 1]);
+        for i in 2..7 {
+            buf.push(i);
+        }
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [4, 5, 6]);
+
+        buf.clear();
+        buf.push(7);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [7]);
+
+        let mut empty = RingBuffer::new(0);
+        empty.push(1);
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,1063 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use rand::rngs::{StdRng, ThreadRng};
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::cube::LastLayerCase;
+
+/// Default scramble length for 2x2, about what other random move scramblers use.
+pub const SCRAMBLE_MOVES_2X2: usize = 9;
+/// Default scramble length for 3x3. Random move scrambles need to be a lot longer than the ~20
+/// moves of WCA random state scrambles to be similarly well mixed.
+pub const SCRAMBLE_MOVES_3X3: usize = 40;
+/// Upper bound for scrambles, so [`Scramble`] can stay `Copy`.
+pub const MAX_SCRAMBLE_MOVES: usize = 100;
+/// Where each `--scramble-file` was left off, in the data directory.
+const POSITIONS_FILE: &str = "scramble-positions.json";
+
+/// The position in a scramble file, and a digest of its scrambles to notice when it changed.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct SavedPosition {
+    digest: u64,
+    index: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Puzzle {
+    TwoByTwo,
+    #[default]
+    ThreeByThree,
+}
+
+impl std::fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TwoByTwo => f.write_str("2x2"),
+            Self::ThreeByThree => f.write_str("3x3"),
+        }
+    }
+}
+
+impl std::str::FromStr for Puzzle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "2x2" => Ok(Self::TwoByTwo),
+            "3x3" => Ok(Self::ThreeByThree),
+            _ => Err(format!("unknown puzzle `{s}`, expected `2x2` or `3x3`")),
+        }
+    }
+}
+
+impl Puzzle {
+    /// Faces that are never turned. On a 2x2 turning a face is the same as turning the
+    /// opposite one and rotating, so only R, U and F are used.
+    fn excluded_dirs(&self) -> u8 {
+        match self {
+            Self::TwoByTwo => Dir::Back as u8 | Dir::Left as u8 | Dir::Down as u8,
+            Self::ThreeByThree => 0,
+        }
+    }
+}
+
+/// The generator of random scrambles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RngKind {
+    /// The thread local generator of `rand`, which can't be seeded.
+    Thread,
+    /// The standard generator of `rand`, whose algorithm may change between versions.
+    Std,
+    /// ChaCha20, which stays the same for a seed across versions, so a `--seed` keeps giving the
+    /// same scrambles after an update.
+    #[default]
+    ChaCha,
+}
+
+impl std::fmt::Display for RngKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Thread => f.write_str("thread"),
+            Self::Std => f.write_str("std"),
+            Self::ChaCha => f.write_str("chacha"),
+        }
+    }
+}
+
+impl std::str::FromStr for RngKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "thread" => Ok(Self::Thread),
+            "std" => Ok(Self::Std),
+            "chacha" => Ok(Self::ChaCha),
+            _ => Err(format!(
+                "unknown rng `{s}`, expected `thread`, `std` or `chacha`"
+            )),
+        }
+    }
+}
+
+/// One of the generators of [`RngKind`].
+#[derive(Clone, Debug)]
+pub enum ScrambleRng {
+    Thread(ThreadRng),
+    Std(StdRng),
+    ChaCha(ChaCha20Rng),
+}
+
+impl Default for ScrambleRng {
+    fn default() -> Self {
+        Self::ChaCha(ChaCha20Rng::from_entropy())
+    }
+}
+
+impl ScrambleRng {
+    /// Seeded with `seed` if given, otherwise from the operating system.
+    pub fn new(kind: RngKind, seed: Option<u64>) -> Result<Self, String> {
+        Ok(match (kind, seed) {
+            (RngKind::Thread, Some(_)) => {
+                return Err("`--seed` can't be used with `--rng thread`".into());
+            }
+            (RngKind::Thread, None) => Self::Thread(rand::thread_rng()),
+            (RngKind::Std, Some(seed)) => Self::Std(StdRng::seed_from_u64(seed)),
+            (RngKind::Std, None) => Self::Std(StdRng::from_entropy()),
+            (RngKind::ChaCha, Some(seed)) => Self::ChaCha(ChaCha20Rng::seed_from_u64(seed)),
+            (RngKind::ChaCha, None) => Self::ChaCha(ChaCha20Rng::from_entropy()),
+        })
+    }
+}
+
+impl RngCore for ScrambleRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Self::Thread(rng) => rng.next_u32(),
+            Self::Std(rng) => rng.next_u32(),
+            Self::ChaCha(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            Self::Thread(rng) => rng.next_u64(),
+            Self::Std(rng) => rng.next_u64(),
+            Self::ChaCha(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            Self::Thread(rng) => rng.fill_bytes(dest),
+            Self::Std(rng) => rng.fill_bytes(dest),
+            Self::ChaCha(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            Self::Thread(rng) => rng.try_fill_bytes(dest),
+            Self::Std(rng) => rng.try_fill_bytes(dest),
+            Self::ChaCha(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+pub fn default_scramble_length(puzzle: Puzzle) -> usize {
+    match puzzle {
+        Puzzle::TwoByTwo => SCRAMBLE_MOVES_2X2,
+        Puzzle::ThreeByThree => SCRAMBLE_MOVES_3X3,
+    }
+}
+
+/// Where new scrambles come from.
+#[derive(Clone, Debug)]
+pub enum ScrambleSource {
+    Random {
+        puzzle: Puzzle,
+        len: usize,
+        /// Seeded with `--seed` to get the same scrambles every time.
+        rng: Box<ScrambleRng>,
+        /// The last served scramble, never served twice in a row.
+        prev: Option<Scramble>,
+        /// How many scrambles were drawn from `rng`, including the ones drawn again because they
+        /// repeated `prev`.
+        draws: usize,
+    },
+    /// Scrambles from a file, served in order and starting over once exhausted.
+    List {
+        scrambles: Vec<Scramble>,
+        next: usize,
+    },
+    /// The same algorithm every time, for practicing execution.
+    Alg(Scramble),
+    /// Scrambles that end in a last layer case, for learning its algorithm.
+    Case {
+        case: LastLayerCase,
+        rng: Box<ScrambleRng>,
+    },
+    /// One scramble per puzzle, solved back to back and timed together.
+    Relay {
+        puzzles: Vec<Puzzle>,
+        /// The current scrambles, in the order of `puzzles`.
+        scrambles: Vec<Scramble>,
+        rng: Box<ScrambleRng>,
+    },
+}
+
+impl Default for ScrambleSource {
+    fn default() -> Self {
+        Self::Random {
+            puzzle: Puzzle::ThreeByThree,
+            len: SCRAMBLE_MOVES_3X3,
+            rng: Box::default(),
+            prev: None,
+            draws: 0,
+        }
+    }
+}
+
+impl ScrambleSource {
+    /// Random scrambles drawn from `rng`, the same sequence every time if it was seeded.
+    pub fn random(puzzle: Puzzle, len: usize, rng: ScrambleRng) -> Self {
+        Self::Random {
+            puzzle,
+            len,
+            rng: Box::new(rng),
+            prev: None,
+            draws: 0,
+        }
+    }
+
+    /// Loads a file with one scramble per line, empty lines are skipped.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("error reading {}: {e}", path.display()))?;
+        let mut scrambles = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let scramble =
+                parse_scramble(line).map_err(|e| format!("{}:{}: {e}", path.display(), i + 1))?;
+            scrambles.push(scramble);
+        }
+        if scrambles.is_empty() {
+            return Err(format!("no scrambles in {}", path.display()).into());
+        }
+        Ok(Self::List { scrambles, next: 0 })
+    }
+
+    pub fn next(&mut self) -> Scramble {
+        match self {
+            Self::Random {
+                puzzle,
+                len,
+                rng,
+                prev,
+                draws,
+            } => {
+                // short scrambles repeat often enough to look like a rescramble did nothing
+                let mut scramble = Scramble::random_with(rng, *puzzle, *len);
+                *draws += 1;
+                while *len > 0 && *prev == Some(scramble) {
+                    scramble = Scramble::random_with(rng, *puzzle, *len);
+                    *draws += 1;
+                }
+                *prev = Some(scramble);
+                scramble
+            }
+            Self::List { scrambles, next } => {
+                let scramble = scrambles[*next];
+                *next = (*next + 1) % scrambles.len();
+                scramble
+            }
+            Self::Alg(alg) => *alg,
+            Self::Case { case, rng } => crate::cube::case_scramble(rng, *case),
+            Self::Relay {
+                puzzles,
+                scrambles,
+                rng,
+            } => {
+                *scrambles = puzzles
+                    .iter()
+                    .map(|&p| Scramble::random_with(rng, p, default_scramble_length(p)))
+                    .collect();
+                scrambles[0]
+            }
+        }
+    }
+
+    /// Continues a list loaded from `path` where the last session left off, as saved in
+    /// `data_dir`. Returns whether the file changed since, then the list starts from the top.
+    pub fn resume(&mut self, path: &Path, data_dir: Option<&Path>) -> bool {
+        let (Some(digest), Some(dir)) = (self.digest(), data_dir) else {
+            return false;
+        };
+        let Some(saved) = load_positions(dir).get(&position_key(path)).copied() else {
+            return false;
+        };
+        if saved.digest != digest {
+            return true;
+        }
+        if let Self::List { scrambles, next } = self {
+            *next = saved.index % scrambles.len();
+        }
+        false
+    }
+
+    /// Stores `index` in `data_dir` as the scramble to continue a list loaded from `path` with.
+    pub fn save_position(
+        &self,
+        path: &Path,
+        index: usize,
+        data_dir: Option<&Path>,
+    ) -> Result<(), Box<dyn Error>> {
+        let (Some(digest), Some(dir)) = (self.digest(), data_dir) else {
+            return Ok(());
+        };
+        let mut positions = load_positions(dir);
+        positions.insert(position_key(path), SavedPosition { digest, index });
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(POSITIONS_FILE), serde_json::to_string(&positions)?)?;
+        Ok(())
+    }
+
+    /// FNV-1a hash of the scrambles of a list, `None` for other sources.
+    fn digest(&self) -> Option<u64> {
+        let Self::List { scrambles, .. } = self else {
+            return None;
+        };
+        let mut hash = 0xcbf29ce484222325_u64;
+        for scramble in scrambles {
+            for byte in format!("{scramble:#}\n").bytes() {
+                hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+            }
+        }
+        Some(hash)
+    }
+
+    /// The 1-based position of the last served scramble in the list, and the list length.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::Random { .. } | Self::Alg(_) | Self::Case { .. } | Self::Relay { .. } => None,
+            Self::List { scrambles, next } => {
+                let pos = (*next + scrambles.len() - 1) % scrambles.len();
+                Some((pos + 1, scrambles.len()))
+            }
+        }
+    }
+
+    /// How many random scrambles were drawn so far, `None` for sources that don't redraw.
+    pub fn draws(&self) -> Option<usize> {
+        match self {
+            Self::Random { draws, .. } => Some(*draws),
+            _ => None,
+        }
+    }
+}
+
+/// The saved positions, none if the file is missing or broken. That only loses where the
+/// scramble files were left off, so it's no reason not to start.
+fn load_positions(data_dir: &Path) -> BTreeMap<PathBuf, SavedPosition> {
+    std::fs::read_to_string(data_dir.join(POSITIONS_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// The same file is found under the same key no matter how the path was written.
+fn position_key(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// A move of some puzzle.
+pub trait ScrambleMove: Copy + Eq + std::fmt::Debug + std::fmt::Display {
+    /// Fills the unused slots of a [`Scramble`].
+    const NONE: Self;
+
+    fn face(&self) -> char;
+
+    /// The modifier written after the face, `None` for a plain turn. Puzzles that only have
+    /// plain and reverse turns never return `'2'`.
+    fn modifier_char(&self) -> Option<char>;
+
+    /// Index of the turned face, used to pick a color.
+    fn face_index(&self) -> u8;
+}
+
+/// Writes the face followed by the modifier. Plain turns are padded with a space so all moves
+/// are equally wide, unless the alternate form `{:#}` is used.
+pub fn fmt_move(mov: &impl ScrambleMove, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_char(mov.face())?;
+    match mov.modifier_char() {
+        Some(c) => f.write_char(c),
+        None if f.alternate() => Ok(()),
+        None => f.write_char(' '),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scramble<M: ScrambleMove = Move> {
+    moves: [M; MAX_SCRAMBLE_MOVES],
+    len: u8,
+}
+
+impl Default for Scramble {
+    fn default() -> Self {
+        Self::random()
+    }
+}
+
+impl<M: ScrambleMove> Scramble<M> {
+    pub fn moves(&self) -> &[M] {
+        &self.moves[..self.len as usize]
+    }
+}
+
+/// The moves separated by spaces, formatted like [`fmt_move`].
+impl<M: ScrambleMove> std::fmt::Display for Scramble<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, mov) in self.moves().iter().enumerate() {
+            if i > 0 {
+                f.write_char(' ')?;
+            }
+            fmt_move(mov, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Scramble {
+    pub fn empty() -> Self {
+        Self {
+            moves: [Move::NONE; MAX_SCRAMBLE_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn random() -> Self {
+        Self::random_for(Puzzle::ThreeByThree, SCRAMBLE_MOVES_3X3)
+    }
+
+    /// A random scramble of `len` moves, at most [`MAX_SCRAMBLE_MOVES`].
+    pub fn random_for(puzzle: Puzzle, len: usize) -> Self {
+        Self::random_with(&mut rand::thread_rng(), puzzle, len)
+    }
+
+    /// Like [`Scramble::random_for`], drawing from `rng`.
+    pub fn random_with(rng: &mut impl Rng, puzzle: Puzzle, len: usize) -> Self {
+        let len = len.min(MAX_SCRAMBLE_MOVES);
+        let mut moves = [Move::NONE; MAX_SCRAMBLE_MOVES];
+        let mut prev_dirs = PrevDirs(0);
+        for mov in &mut moves[..len] {
+            *mov = Move::random(rng, PrevDirs(prev_dirs.0 | puzzle.excluded_dirs()));
+            prev_dirs.update(mov.dir());
+        }
+        Self {
+            moves,
+            len: len as u8,
+        }
+    }
+
+    /// The moves undoing this scramble.
+    pub fn inverse(&self) -> Self {
+        let mut inverse = Self::empty();
+        for mov in self.moves().iter().rev() {
+            inverse.push(mov.inverse());
+        }
+        inverse
+    }
+
+    /// Appends `mov`, merged with the last move if both turn the same face. Moves beyond
+    /// [`MAX_SCRAMBLE_MOVES`] are dropped.
+    pub fn push(&mut self, mov: Move) {
+        if let Some(last) = self
+            .moves()
+            .last()
+            .filter(|m| m.face_index() == mov.face_index())
+        {
+            let merged = Move::turns(mov.dir(), last.quarter_turns() + mov.quarter_turns());
+            self.len -= 1;
+            if let Some(merged) = merged {
+                self.push(merged);
+            }
+        } else if (self.len as usize) < MAX_SCRAMBLE_MOVES {
+            self.moves[self.len as usize] = mov;
+            self.len += 1;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move(u8);
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_move(self, f)
+    }
+}
+
+impl ScrambleMove for Move {
+    const NONE: Self = Self(0);
+
+    fn face(&self) -> char {
+        match self.dir() {
+            Dir::Front => 'F',
+            Dir::Back => 'B',
+            Dir::Left => 'L',
+            Dir::Right => 'R',
+            Dir::Up => 'U',
+            Dir::Down => 'D',
+        }
+    }
+
+    fn modifier_char(&self) -> Option<char> {
+        match self.modifier() {
+            Mod::Forward => None,
+            Mod::Reverse => Some('\''),
+            Mod::Double => Some('2'),
+        }
+    }
+
+    fn face_index(&self) -> u8 {
+        (self.dir() as u8).trailing_zeros() as u8
+    }
+}
+
+impl Move {
+    #[rustfmt::skip]
+    const DOUBLE: u8   = 0b1000_0000;
+    #[rustfmt::skip]
+    const REVERSE: u8  = 0b0100_0000;
+    #[rustfmt::skip]
+    const DIR_MASK: u8 = 0b0011_1111;
+
+    pub fn random(rng: &mut impl Rng, prev_dirs: PrevDirs) -> Self {
+        let mut mov = 0;
+
+        // Stray bits outside the faces would throw off the count, and with every face excluded
+        // there would be nothing to pick, leaving an invalid direction.
+        let mut excluded = PrevDirs(prev_dirs.0 & Self::DIR_MASK);
+        if excluded.0 == Self::DIR_MASK {
+            excluded.0 = 0;
+        }
+        let num_dirs = 6 - excluded.0.count_ones() as u8;
+        let mut dir: u8 = rng.gen_range(0..num_dirs);
+
+        for i in 0..6 {
+            let bit = 1 << i;
+            if !excluded.get(bit) {
+                if dir == 0 {
+                    mov |= bit;
+                    break;
+                }
+
+                dir -= 1;
+            }
+        }
+
+        debug_assert_eq!(mov.count_ones(), 1);
+
+        let modifier: u8 = rng.gen_range(0..3);
+        match modifier {
+            0 => (),
+            1 => mov |= Self::REVERSE,
+            _ => mov |= Self::DOUBLE,
+        }
+
+        Self(mov)
+    }
+
+    /// `turns` clockwise quarter turns of `dir`, `None` for a multiple of 4.
+    pub fn turns(dir: Dir, turns: u8) -> Option<Self> {
+        match turns % 4 {
+            0 => None,
+            1 => Some(Self(dir as u8)),
+            2 => Some(Self(dir as u8 | Self::DOUBLE)),
+            _ => Some(Self(dir as u8 | Self::REVERSE)),
+        }
+    }
+
+    /// Clockwise quarter turns in `1..=3`.
+    pub fn quarter_turns(&self) -> u8 {
+        match self.modifier() {
+            Mod::Forward => 1,
+            Mod::Double => 2,
+            Mod::Reverse => 3,
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        match self.modifier() {
+            Mod::Forward => Self(self.0 | Self::REVERSE),
+            Mod::Reverse => Self(self.0 & !Self::REVERSE),
+            Mod::Double => *self,
+        }
+    }
+
+    pub fn dir(&self) -> Dir {
+        let dir = self.0 & Self::DIR_MASK;
+        // SAFETY: Dir is repr(u8)
+        unsafe { std::mem::transmute(dir) }
+    }
+
+    pub fn modifier(&self) -> Mod {
+        if (self.0 & Self::DOUBLE) != 0 {
+            return Mod::Double;
+        }
+        match (self.0 & Self::REVERSE) != 0 {
+            true => Mod::Reverse,
+            false => Mod::Forward,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct PrevDirs(u8);
+
+impl PrevDirs {
+    fn update(&mut self, dir: Dir) {
+        self.0 &= match dir {
+            Dir::Front | Dir::Back => Dir::Front as u8 | Dir::Back as u8,
+            Dir::Left | Dir::Right => Dir::Left as u8 | Dir::Right as u8,
+            Dir::Up | Dir::Down => Dir::Up as u8 | Dir::Down as u8,
+        };
+        self.0 |= dir as u8;
+    }
+
+    fn get(&self, bit: u8) -> bool {
+        (self.0 & bit) != 0
+    }
+}
+
+#[repr(u8)]
+#[rustfmt::skip]
+pub enum Dir {
+    Front = 1 << 0,
+    Back  = 1 << 1,
+    Left  = 1 << 2,
+    Right = 1 << 3,
+    Up    = 1 << 4,
+    Down  = 1 << 5,
+}
+
+pub enum Mod {
+    Forward,
+    Reverse,
+    Double,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Easy => f.write_str("easy"),
+            Self::Medium => f.write_str("medium"),
+            Self::Hard => f.write_str("hard"),
+        }
+    }
+}
+
+/// A rough guess based on the share of quarter turns. Half turns never change the orientation
+/// of edges or corners, so the more of them a scramble has the closer the cube stays to a
+/// solved orientation. Random moves are quarter turns 2/3 of the time, for a 40 move scramble
+/// the share has a standard deviation of about 0.075, which the thresholds are based on.
+pub fn estimate_difficulty(scramble: &Scramble) -> Difficulty {
+    let moves = scramble.moves();
+    let quarter_turns = moves
+        .iter()
+        .filter(|m| !matches!(m.modifier(), Mod::Double))
+        .count();
+    let share = quarter_turns as f32 / moves.len().max(1) as f32;
+    if share < 0.6 {
+        Difficulty::Easy
+    } else if share > 0.74 {
+        Difficulty::Hard
+    } else {
+        Difficulty::Medium
+    }
+}
+
+/// How often each face and modifier came up in the scrambles added so far.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScrambleStats {
+    pub scrambles: usize,
+    /// Indexed by [`ScrambleMove::face_index`].
+    pub faces: [usize; 6],
+    /// Plain, reverse and double turns.
+    pub modifiers: [usize; 3],
+}
+
+impl ScrambleStats {
+    /// Face letters in the order of [`ScrambleStats::faces`].
+    pub const FACES: [char; 6] = ['F', 'B', 'L', 'R', 'U', 'D'];
+
+    pub fn add<M: ScrambleMove>(&mut self, scramble: &Scramble<M>) {
+        self.scrambles += 1;
+        for mov in scramble.moves() {
+            self.faces[mov.face_index() as usize] += 1;
+            let modifier = match mov.modifier_char() {
+                None => 0,
+                Some('\'') => 1,
+                Some(_) => 2,
+            };
+            self.modifiers[modifier] += 1;
+        }
+    }
+
+    pub fn moves(&self) -> usize {
+        self.faces.iter().sum()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    msg: String,
+    input: String,
+    /// Byte index of `token` in `input`.
+    pub index: usize,
+    /// The offending part of the input, up to the next whitespace.
+    pub token: String,
+}
+
+impl ParseError {
+    fn new(msg: String, input: &str, index: usize) -> Self {
+        let rest = &input[index..];
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        Self {
+            msg,
+            input: input.to_string(),
+            index,
+            token: rest[..end].to_string(),
+        }
+    }
+}
+
+/// The message followed by the input, with the offending token underlined by carets.
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let column = self.input[..self.index].chars().count();
+        let width = self.token.chars().count().max(1);
+        writeln!(f, "{} at column {}", self.msg, column + 1)?;
+        writeln!(f, "  {}", self.input)?;
+        write!(f, "  {}{}", " ".repeat(column), "^".repeat(width))
+    }
+}
+
+impl Error for ParseError {}
+
+fn parse_face(c: char) -> Option<Dir> {
+    match c {
+        'F' => Some(Dir::Front),
+        'B' => Some(Dir::Back),
+        'L' => Some(Dir::Left),
+        'R' => Some(Dir::Right),
+        'U' => Some(Dir::Up),
+        'D' => Some(Dir::Down),
+        _ => None,
+    }
+}
+
+/// Parses moves like `R U' F2`, whitespace between moves is optional.
+pub fn parse_scramble(input: &str) -> Result<Scramble, ParseError> {
+    let mut moves = [Move::NONE; MAX_SCRAMBLE_MOVES];
+    let mut len = 0;
+    let mut chars = input.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let Some(dir) = parse_face(c) else {
+            let msg = format!("unexpected character `{c}`");
+            return Err(ParseError::new(msg, input, start));
+        };
+
+        let mut mov = dir as u8;
+        if chars.next_if(|&(_, c)| c == '2').is_some() {
+            mov |= Move::DOUBLE;
+            chars.next_if(|&(_, c)| c == '\'');
+        } else if chars.next_if(|&(_, c)| c == '\'').is_some() {
+            mov |= Move::REVERSE;
+        }
+
+        // anything else directly after a move is a modifier we don't know
+        if let Some(&(i, c)) = chars.peek() {
+            if !c.is_whitespace() && parse_face(c).is_none() {
+                let msg = format!("invalid modifier `{c}` for `{:#}`", Move(mov));
+                return Err(ParseError::new(msg, input, i));
+            }
+        }
+
+        if len == MAX_SCRAMBLE_MOVES {
+            let msg = format!("scramble is longer than {MAX_SCRAMBLE_MOVES} moves");
+            return Err(ParseError::new(msg, input, start));
+        }
+        moves[len] = Move(mov);
+        len += 1;
+    }
+
+    Ok(Scramble {
+        moves,
+        len: len as u8,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory only used by the test `name`.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cube-tuimer-{name}-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn resume_scramble_file() {
+        let dir = temp_dir("resume");
+        let data_dir = Some(dir.as_path());
+        let path = dir.join("scrambles.txt");
+        std::fs::write(&path, "R U\nF2\nL' D\n").unwrap();
+
+        let scrambles = ScrambleSource::load(&path).unwrap();
+        scrambles.save_position(&path, 2, data_dir).unwrap();
+        let mut scrambles = ScrambleSource::load(&path).unwrap();
+        assert!(!scrambles.resume(&path, data_dir));
+        assert_eq!(scrambles.next(), parse_scramble("L' D").unwrap());
+
+        std::fs::write(&path, "R U\nF2\nL D\n").unwrap();
+        let mut scrambles = ScrambleSource::load(&path).unwrap();
+        assert!(scrambles.resume(&path, data_dir));
+        assert_eq!(scrambles.next(), parse_scramble("R U").unwrap());
+
+        // a broken positions file is as good as none, and replaced on the next save
+        std::fs::write(dir.join(POSITIONS_FILE), "{\"trunc").unwrap();
+        let mut scrambles = ScrambleSource::load(&path).unwrap();
+        assert!(!scrambles.resume(&path, data_dir));
+        assert_eq!(scrambles.next(), parse_scramble("R U").unwrap());
+        scrambles.save_position(&path, 1, data_dir).unwrap();
+        let mut scrambles = ScrambleSource::load(&path).unwrap();
+        scrambles.resume(&path, data_dir);
+        assert_eq!(scrambles.next(), parse_scramble("F2").unwrap());
+    }
+
+    const ALL_MOVES: &str = "F F' F2 B B' B2 L L' L2 R R' R2 U U' U2 D D' D2";
+
+    fn seeded(seed: u64, puzzle: Puzzle) -> Scramble {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        Scramble::random_with(&mut rng, puzzle, default_scramble_length(puzzle))
+    }
+
+    fn compact(scramble: &Scramble) -> String {
+        let moves: Vec<_> = scramble.moves().iter().map(|m| format!("{m:#}")).collect();
+        moves.join(" ")
+    }
+
+    /// Catches unintended changes to the generator, which would change the scrambles served
+    /// for a `--seed`. Update the snapshots if the change is intended.
+    #[test]
+    fn random_snapshot() {
+        assert_eq!(
+            compact(&seeded(42, Puzzle::ThreeByThree)),
+            "D' R' F U R D' F2 D' L R' D U2 R2 L' D2 B F U' D B' \
+             D F' L2 B2 F' U2 R' U F D2 L R' B' L' F B2 D' B D' R2"
+        );
+        assert_eq!(
+            compact(&seeded(42, Puzzle::TwoByTwo)),
+            "U' R' F U R U' F2 U' F"
+        );
+    }
+
+    #[test]
+    fn random_source_never_repeats() {
+        for puzzle in [Puzzle::TwoByTwo, Puzzle::ThreeByThree] {
+            let rng = ScrambleRng::new(RngKind::Std, Some(7)).unwrap();
+            let mut source = ScrambleSource::random(puzzle, 1, rng);
+            let mut prev = source.next();
+            for _ in 0..500 {
+                let scramble = source.next();
+                assert_ne!(scramble, prev);
+                prev = scramble;
+            }
+        }
+
+        let rng = ScrambleRng::new(RngKind::Std, Some(7)).unwrap();
+        let mut source = ScrambleSource::random(Puzzle::ThreeByThree, 0, rng);
+        assert_eq!(source.next(), source.next());
+    }
+
+    #[test]
+    fn seeded_rngs() {
+        for kind in [RngKind::Std, RngKind::ChaCha] {
+            let source = || {
+                let rng = ScrambleRng::new(kind, Some(42)).unwrap();
+                ScrambleSource::random(Puzzle::ThreeByThree, SCRAMBLE_MOVES_3X3, rng)
+            };
+            let (mut a, mut b) = (source(), source());
+            for _ in 0..10 {
+                assert_eq!(a.next(), b.next());
+            }
+        }
+        assert!(ScrambleRng::new(RngKind::Thread, Some(42)).is_err());
+        assert!(ScrambleRng::new(RngKind::Thread, None).is_ok());
+    }
+
+    #[test]
+    fn seeded_relay() {
+        let source = || ScrambleSource::Relay {
+            puzzles: vec![Puzzle::TwoByTwo, Puzzle::ThreeByThree],
+            scrambles: Vec::new(),
+            rng: Box::new(ScrambleRng::new(RngKind::ChaCha, Some(42)).unwrap()),
+        };
+        let (mut a, mut b) = (source(), source());
+        for _ in 0..10 {
+            a.next();
+            b.next();
+            let (
+                ScrambleSource::Relay { scrambles: a, .. },
+                ScrambleSource::Relay { scrambles: b, .. },
+            ) = (&a, &b)
+            else {
+                unreachable!();
+            };
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn random_invariants() {
+        for seed in 0..200 {
+            for puzzle in [Puzzle::TwoByTwo, Puzzle::ThreeByThree] {
+                let scramble = seeded(seed, puzzle);
+                assert_eq!(scramble.moves().len(), default_scramble_length(puzzle));
+
+                // faces turned since the last change of axis
+                let mut axis_run = 0;
+                for mov in scramble.moves() {
+                    let dir = mov.0 & Move::DIR_MASK;
+                    let msg = format!("seed {seed}, {puzzle}: {}", compact(&scramble));
+                    assert_eq!(dir.count_ones(), 1, "{msg}");
+                    assert_eq!(dir & puzzle.excluded_dirs(), 0, "{msg}");
+                    assert_eq!(dir & axis_run, 0, "{msg}");
+
+                    let axis = match mov.dir() {
+                        Dir::Front | Dir::Back => Dir::Front as u8 | Dir::Back as u8,
+                        Dir::Left | Dir::Right => Dir::Left as u8 | Dir::Right as u8,
+                        Dir::Up | Dir::Down => Dir::Up as u8 | Dir::Down as u8,
+                    };
+                    axis_run = (axis_run & axis) | dir;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let cases = [
+            // bad face
+            ("R X U", "unexpected character `X`", 2, "X"),
+            ("R U r2", "unexpected character `r`", 4, "r2"),
+            // bad modifier
+            ("R U3 F", "invalid modifier `3` for `U`", 3, "3"),
+            ("R2'' U", "invalid modifier `'` for `R2`", 3, "'"),
+            // trailing junk
+            ("R U F.", "invalid modifier `.` for `F`", 5, "."),
+            ("R U F !?", "unexpected character `!`", 6, "!?"),
+        ];
+        for (input, msg, index, token) in cases {
+            let err = parse_scramble(input).unwrap_err();
+            assert_eq!(
+                (err.msg.as_str(), err.index, err.token.as_str()),
+                (msg, index, token)
+            );
+        }
+
+        let err = parse_scramble("R U3 F").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid modifier `3` for `U` at column 4\n  R U3 F\n     ^"
+        );
+    }
+
+    #[test]
+    fn random_valid_dir() {
+        let mut rng = rand::thread_rng();
+        for prev in 0..=u8::MAX {
+            let excluded = match prev & Move::DIR_MASK {
+                Move::DIR_MASK => 0,
+                e => e,
+            };
+            for _ in 0..32 {
+                let mov = Move::random(&mut rng, PrevDirs(prev));
+                let dir = mov.0 & Move::DIR_MASK;
+                assert_eq!(dir.count_ones(), 1, "prev_dirs {prev:#010b}");
+                assert_eq!(dir & excluded, 0, "prev_dirs {prev:#010b}");
+            }
+        }
+    }
+
+    #[test]
+    fn faces_are_uniform_after_each_face() {
+        let mut rng = StdRng::seed_from_u64(198);
+        // counts[prev][next] of consecutive faces
+        let mut counts = [[0_u32; 6]; 6];
+        for _ in 0..2000 {
+            let scramble = Scramble::random_with(&mut rng, Puzzle::ThreeByThree, 40);
+            for pair in scramble.moves().windows(2) {
+                counts[pair[0].face_index() as usize][pair[1].face_index() as usize] += 1;
+            }
+        }
+
+        let close = |a: u32, b: u32| a.abs_diff(b) * 10 < a.max(b);
+        for (prev, next) in counts.iter().enumerate() {
+            let axis = prev / 2;
+            let others: Vec<_> = (0..6).filter(|f| f / 2 != axis).map(|f| next[f]).collect();
+            let mean = others.iter().sum::<u32>() / others.len() as u32;
+            for &count in &others {
+                assert!(close(count, mean), "after face {prev}: {next:?}");
+            }
+            assert_eq!(next[prev], 0);
+            // the opposite face is allowed unless it came right before, the same either way
+            let opposite = prev ^ 1;
+            assert!(close(next[opposite], counts[opposite][prev]), "{counts:?}");
+        }
+    }
+
+    #[test]
+    fn display_padded() {
+        let scramble = parse_scramble(ALL_MOVES).unwrap();
+        let moves: Vec<_> = scramble.moves().iter().map(|m| m.to_string()).collect();
+        assert_eq!(
+            moves.join(" "),
+            "F  F' F2 B  B' B2 L  L' L2 R  R' R2 U  U' U2 D  D' D2"
+        );
+    }
+
+    #[test]
+    fn display_compact() {
+        let scramble = parse_scramble(ALL_MOVES).unwrap();
+        let moves: Vec<_> = scramble.moves().iter().map(|m| format!("{m:#}")).collect();
+        assert_eq!(moves.join(" "), ALL_MOVES);
+    }
+
+    #[test]
+    fn display_encoding() {
+        assert_eq!(Move(Dir::Right as u8).to_string(), "R ");
+        assert_eq!(Move(Dir::Up as u8 | Move::REVERSE).to_string(), "U'");
+        assert_eq!(Move(Dir::Back as u8 | Move::DOUBLE).to_string(), "B2");
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+use crate::scramble::{Puzzle, Scramble, ScrambleMove};
+
+const CORNERS: usize = 8;
+/// Permutations of the corners.
+const PERMS: usize = 40320;
+/// Orientations of the first 7 corners, the last one follows from them.
+const ORIENTATIONS: usize = 2187;
+
+/// Each face turn clockwise in R, U, F order, as the corner that ends up in each position and
+/// how much it gets twisted.
+const FACE_TURNS: [Corners; 3] = [
+    Corners {
+        perm: [4, 1, 2, 0, 7, 5, 6, 3],
+        orientation: [2, 0, 0, 1, 1, 0, 0, 2],
+    },
+    Corners {
+        perm: [3, 0, 1, 2, 4, 5, 6, 7],
+        orientation: [0; 8],
+    },
+    Corners {
+        perm: [1, 5, 2, 3, 0, 4, 6, 7],
+        orientation: [1, 2, 0, 0, 2, 1, 0, 0],
+    },
+];
+
+/// The corners of a 2x2, numbered `URF UFL ULB UBR DFR DLF DBL DRB`. `DBL` never moves since
+/// 2x2 scrambles only turn R, U and F.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Corners {
+    perm: [u8; CORNERS],
+    orientation: [u8; CORNERS],
+}
+
+impl Corners {
+    const SOLVED: Self = Self {
+        perm: [0, 1, 2, 3, 4, 5, 6, 7],
+        orientation: [0; CORNERS],
+    };
+
+    fn turn(&self, face: usize) -> Self {
+        let turn = &FACE_TURNS[face];
+        let mut next = *self;
+        for i in 0..CORNERS {
+            let from = turn.perm[i] as usize;
+            next.perm[i] = self.perm[from];
+            next.orientation[i] = (self.orientation[from] + turn.orientation[i]) % 3;
+        }
+        next
+    }
+
+    /// Lehmer code of the permutation.
+    fn perm_index(&self) -> usize {
+        let mut index = 0;
+        for i in 0..CORNERS {
+            let smaller = self.perm[i + 1..]
+                .iter()
+                .filter(|&&p| p < self.perm[i])
+                .count();
+            index = index * (CORNERS - i) + smaller;
+        }
+        index
+    }
+
+    fn orientation_index(&self) -> usize {
+        let twists = &self.orientation[..CORNERS - 1];
+        twists.iter().fold(0, |index, &o| index * 3 + o as usize)
+    }
+}
+
+/// Number of moves of an optimal solution for a 2x2 scramble. `None` for other puzzles, which
+/// are too big to solve here, and scrambles that turn faces other than R, U and F.
+pub fn solution_hint(scramble: &Scramble, puzzle: Puzzle) -> Option<usize> {
+    if puzzle != Puzzle::TwoByTwo {
+        return None;
+    }
+    solve(scramble).map(|solution| solution.len())
+}
+
+/// An optimal solution as `(face, quarter turns)` pairs, found with IDA*.
+fn solve(scramble: &Scramble) -> Option<Vec<(usize, u8)>> {
+    let mut state = Corners::SOLVED;
+    for mov in scramble.moves() {
+        let face = "RUF".find(mov.face())?;
+        for _ in 0..mov.quarter_turns() {
+            state = state.turn(face);
+        }
+    }
+
+    let (perm_depths, orientation_depths) = depths();
+    let bound = |state: &Corners| {
+        let perm = perm_depths[state.perm_index()];
+        let orientation = orientation_depths[state.orientation_index()];
+        perm.max(orientation) as usize
+    };
+    let mut solution = Vec::new();
+    let mut max_depth = bound(&state);
+    while !search(state, max_depth, None, &bound, &mut solution) {
+        max_depth += 1;
+    }
+    Some(solution)
+}
+
+/// Depth first search for a solution of at most `depth` moves, appended to `solution`.
+fn search(
+    state: Corners,
+    depth: usize,
+    last_face: Option<usize>,
+    bound: &impl Fn(&Corners) -> usize,
+    solution: &mut Vec<(usize, u8)>,
+) -> bool {
+    if state == Corners::SOLVED {
+        return true;
+    }
+    if bound(&state) > depth {
+        return false;
+    }
+    for face in (0..FACE_TURNS.len()).filter(|&f| Some(f) != last_face) {
+        let mut next = state;
+        for turns in 1..=3 {
+            next = next.turn(face);
+            solution.push((face, turns));
+            if search(next, depth - 1, Some(face), bound, solution) {
+                return true;
+            }
+            solution.pop();
+        }
+    }
+    false
+}
+
+/// The fewest moves to solve the permutation and the orientation, each on their own, which
+/// never overestimates the moves needed for both.
+fn depths() -> &'static (Vec<u8>, Vec<u8>) {
+    static DEPTHS: OnceLock<(Vec<u8>, Vec<u8>)> = OnceLock::new();
+    DEPTHS.get_or_init(|| {
+        (
+            breadth_first(PERMS, Corners::perm_index),
+            breadth_first(ORIENTATIONS, Corners::orientation_index),
+        )
+    })
+}
+
+/// Depths of all states reachable from the solved one, by `index`.
+fn breadth_first(len: usize, index: fn(&Corners) -> usize) -> Vec<u8> {
+    let mut depths = vec![u8::MAX; len];
+    let mut queue = VecDeque::from([(Corners::SOLVED, 0)]);
+    depths[index(&Corners::SOLVED)] = 0;
+    while let Some((state, depth)) = queue.pop_front() {
+        for face in 0..FACE_TURNS.len() {
+            let mut next = state;
+            for _ in 1..=3 {
+                next = next.turn(face);
+                let i = index(&next);
+                if depths[i] == u8::MAX {
+                    depths[i] = depth + 1;
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+    }
+    depths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::CubeState;
+    use crate::scramble::parse_scramble;
+
+    #[test]
+    fn short_scrambles() {
+        let hint = |moves: &str| solution_hint(&parse_scramble(moves).unwrap(), Puzzle::TwoByTwo);
+        assert_eq!(hint(""), Some(0));
+        assert_eq!(hint("R"), Some(1));
+        assert_eq!(hint("R U2"), Some(2));
+        assert_eq!(hint("R U F"), Some(3));
+        assert_eq!(hint("R R'"), Some(0));
+        assert_eq!(hint("L"), None);
+        assert_eq!(
+            solution_hint(&parse_scramble("R").unwrap(), Puzzle::ThreeByThree),
+            None
+        );
+    }
+
+    #[test]
+    fn solutions_solve_the_corners() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..5 {
+            let scramble = Scramble::random_with(&mut rng, Puzzle::TwoByTwo, 11);
+            let solution = solve(&scramble).unwrap();
+            // 11 moves are enough for any 2x2 state
+            assert!(solution.len() <= 11);
+
+            let mut state = CubeState::scrambled(&scramble);
+            for &(face, turns) in &solution {
+                let mov = parse_scramble(&"RUF"[face..=face]).unwrap().moves()[0];
+                for _ in 0..turns {
+                    state.apply(mov);
+                }
+            }
+            for (face, colors) in state.faces().iter().enumerate() {
+                for corner in [0, 2, 6, 8] {
+                    assert_eq!(colors[corner], face as u8, "{scramble}");
+                }
+            }
+        }
+    }
+}
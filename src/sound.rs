@@ -0,0 +1,40 @@
+use std::path::Path;
+
+/// Starts playing the sound file in the background. Returns whether the file could be decoded
+/// and an audio device opened, not whether it finished playing.
+#[cfg(feature = "sound")]
+pub fn play(path: &Path) -> bool {
+    use std::io::BufReader;
+    use std::sync::mpsc;
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let Ok(source) = rodio::Decoder::new(BufReader::new(file)) else {
+        return false;
+    };
+
+    // the output stream can't be moved between threads, so it is opened on the one that keeps
+    // it alive until the sound is over
+    let (started, result) = mpsc::channel();
+    std::thread::spawn(move || {
+        let Ok((_stream, handle)) = rodio::OutputStream::try_default() else {
+            _ = started.send(false);
+            return;
+        };
+        let Ok(sink) = rodio::Sink::try_new(&handle) else {
+            _ = started.send(false);
+            return;
+        };
+        sink.append(source);
+        _ = started.send(true);
+        sink.sleep_until_end();
+    });
+    result.recv().unwrap_or(false)
+}
+
+/// Sounds need a build with the `sound` feature, without it there is only the bell.
+#[cfg(not(feature = "sound"))]
+pub fn play(_path: &Path) -> bool {
+    false
+}
@@ -0,0 +1,385 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::history::SolveResult;
+
+/// Most [`histogram`] buckets when filling in the empty ones, wider spreads only get the
+/// buckets that have solves.
+const MAX_FILLED_BUCKETS: u64 = 100;
+
+/// The solves that count towards stats, i.e. all but warmup solves.
+pub fn counted(solves: &[SolveResult]) -> impl DoubleEndedIterator<Item = &SolveResult> {
+    solves.iter().filter(|s| !s.warmup)
+}
+
+/// Mean of all non-DNF solves.
+pub fn mean(solves: &[SolveResult]) -> Option<Duration> {
+    let times: Vec<_> = counted(solves)
+        .filter_map(SolveResult::final_time)
+        .collect();
+    if times.is_empty() {
+        return None;
+    }
+    Some(times.iter().sum::<Duration>() / times.len() as u32)
+}
+
+/// Mean of all non-DNF solves but the slowest one. Not an official statistic, just a more
+/// forgiving one. `None` with fewer than 2 non-DNF solves.
+pub fn mean_without_worst(solves: &[SolveResult]) -> Option<Duration> {
+    let times: Vec<_> = counted(solves)
+        .filter_map(SolveResult::final_time)
+        .collect();
+    let worst = *times.iter().max()?;
+    if times.len() < 2 {
+        return None;
+    }
+    Some((times.iter().sum::<Duration>() - worst) / (times.len() - 1) as u32)
+}
+
+/// Average of the last `n` solves, with the best and worst solve dropped. A DNF counts as the
+/// worst solve, so more than one DNF makes the whole average a DNF, returned as `Some(None)`.
+/// `None` if there are fewer than `n` solves.
+///
+/// Exactly one solve is dropped on each end even if several tie, the earliest of them.
+pub fn average(solves: &[SolveResult], n: usize) -> Option<Option<Duration>> {
+    let solves: Vec<_> = counted(solves).collect();
+    if n < 3 || solves.len() < n {
+        return None;
+    }
+    let times: Vec<_> = solves[solves.len() - n..]
+        .iter()
+        .map(|s| s.final_time().unwrap_or(Duration::MAX))
+        .collect();
+    let mut indices: Vec<_> = (0..n).collect();
+    indices.sort_by_key(|&i| (times[i], i));
+    let counted: Vec<_> = indices[1..n - 1].iter().map(|&i| times[i]).collect();
+    if counted.contains(&Duration::MAX) {
+        return Some(None);
+    }
+    Some(Some(
+        counted.iter().sum::<Duration>() / counted.len() as u32,
+    ))
+}
+
+/// The best of all averages of `n` consecutive solves, ignoring DNF averages.
+pub fn best_average(solves: &[SolveResult], n: usize) -> Option<Duration> {
+    let solves: Vec<_> = counted(solves).cloned().collect();
+    (n..=solves.len())
+        .filter_map(|end| average(&solves[end - n..end], n).flatten())
+        .min()
+}
+
+/// The time the next solve has to beat for the ao5 to be faster than `target`. `None` if no
+/// time would do, or there are fewer than 4 solves. A next solve slower than the last 4 is
+/// dropped as the worst, so if the returned time is slower than all of them any time will do.
+pub fn ao5_target(solves: &[SolveResult], target: Duration) -> Option<Duration> {
+    let mut last: Vec<_> = counted(solves)
+        .rev()
+        .take(4)
+        .map(|s| s.final_time().unwrap_or(Duration::MAX))
+        .collect();
+    if last.len() < 4 {
+        return None;
+    }
+    last.sort();
+    // the next solve counts with the 2nd and 3rd fastest of the last 4, unless it is faster
+    // than all of them and dropped, which gives the best possible average
+    let needed = (target * 3).checked_sub(last[1])?.checked_sub(last[2])?;
+    (needed > last[0]).then_some(needed)
+}
+
+/// Mean of the last 3 solves without dropping any, as used for events with few attempts. Any
+/// DNF makes it a DNF, returned as `Some(None)`. `None` if there are fewer than 3 solves.
+pub fn mo3(solves: &[SolveResult]) -> Option<Option<Duration>> {
+    let last: Vec<_> = counted(solves).rev().take(3).collect();
+    if last.len() < 3 {
+        return None;
+    }
+    let times: Option<Vec<_>> = last.into_iter().map(SolveResult::final_time).collect();
+    Some(times.map(|t| t.iter().sum::<Duration>() / 3))
+}
+
+/// Means of the first and second half of the non-DNF solves, to see whether the times
+/// improved. The middle solve of an odd count is left out. `None` with fewer than 4 solves,
+/// where each half would be a single solve.
+pub fn improvement(solves: &[SolveResult]) -> Option<(Duration, Duration)> {
+    let times: Vec<_> = counted(solves)
+        .filter_map(SolveResult::final_time)
+        .collect();
+    if times.len() < 4 {
+        return None;
+    }
+    let half = times.len() / 2;
+    let mean = |times: &[Duration]| times.iter().sum::<Duration>() / times.len() as u32;
+    Some((mean(&times[..half]), mean(&times[times.len() - half..])))
+}
+
+/// Exponentially weighted moving average of the non-DNF solves, as an estimate of the current
+/// form. The newest solve is weighted by `alpha`, and the weight of older ones decays by
+/// `1 - alpha` per solve.
+pub fn ewma(solves: &[SolveResult], alpha: f32) -> Option<Duration> {
+    let mut times = counted(solves).filter_map(SolveResult::final_time);
+    let first = times.next()?.as_secs_f64();
+    let alpha = alpha as f64;
+    let average = times.fold(first, |avg, t| {
+        alpha * t.as_secs_f64() + (1.0 - alpha) * avg
+    });
+    Some(Duration::from_secs_f64(average))
+}
+
+/// Standard deviation of all non-DNF solves.
+pub fn stddev(solves: &[SolveResult]) -> Option<Duration> {
+    let mean = mean(solves)?.as_secs_f64();
+    let times: Vec<_> = counted(solves)
+        .filter_map(SolveResult::final_time)
+        .collect();
+    let variance = times
+        .iter()
+        .map(|t| (t.as_secs_f64() - mean).powi(2))
+        .sum::<f64>()
+        / times.len() as f64;
+    Some(Duration::from_secs_f64(variance.sqrt()))
+}
+
+/// Counts solves per `bucket` sized time range, keyed by the start of the range. Empty buckets
+/// between the fastest and slowest solve are included, unless there would be more than
+/// [`MAX_FILLED_BUCKETS`]. DNFs are counted in a final bucket keyed by [`Duration::MAX`].
+pub fn histogram(solves: &[SolveResult], bucket: Duration) -> Vec<(Duration, usize)> {
+    let bucket_nanos = bucket.as_nanos().max(1);
+    let mut counts = BTreeMap::new();
+    for time in counted(solves).filter_map(SolveResult::final_time) {
+        *counts.entry(time.as_nanos() / bucket_nanos).or_insert(0) += 1;
+    }
+    // never later than the solve that fell into the bucket, so it can't overflow
+    let start = |i: u128| {
+        let nanos = i * bucket_nanos;
+        Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        )
+    };
+
+    let mut buckets = Vec::new();
+    if let (Some((&min, _)), Some((&max, _))) = (counts.first_key_value(), counts.last_key_value())
+    {
+        match max - min < MAX_FILLED_BUCKETS as u128 {
+            true => {
+                for i in min..=max {
+                    buckets.push((start(i), counts.get(&i).copied().unwrap_or(0)));
+                }
+            }
+            false => buckets.extend(counts.iter().map(|(&i, &count)| (start(i), count))),
+        }
+    }
+
+    let dnfs = counted(solves).filter(|s| s.final_time().is_none()).count();
+    if dnfs > 0 {
+        buckets.push((Duration::MAX, dnfs));
+    }
+
+    buckets
+}
+
+/// Time between the ends of consecutive solves. Solves whose timestamps go backwards, e.g.
+/// after the clock was changed, are skipped.
+pub fn solve_intervals(solves: &[SolveResult]) -> Vec<Duration> {
+    solves
+        .windows(2)
+        .filter_map(|w| w[1].timestamp.duration_since(w[0].timestamp).ok())
+        .collect()
+}
+
+/// Median and longest of the `intervals` shorter than `break_after`, and the number of longer
+/// ones, which are counted as breaks. `None` without any interval shorter than `break_after`.
+pub fn gaps(intervals: &[Duration], break_after: Duration) -> Option<(Duration, Duration, usize)> {
+    let mut gaps: Vec<_> = intervals
+        .iter()
+        .copied()
+        .filter(|&i| i < break_after)
+        .collect();
+    gaps.sort();
+    let breaks = intervals.len() - gaps.len();
+    let longest = *gaps.last()?;
+    let median = match gaps.len() % 2 {
+        0 => (gaps[gaps.len() / 2 - 1] + gaps[gaps.len() / 2]) / 2,
+        _ => gaps[gaps.len() / 2],
+    };
+    Some((median, longest, breaks))
+}
+
+/// Whether the solve is not a DNF and at most `target`.
+pub fn beats(solve: &SolveResult, target: Duration) -> bool {
+    solve.final_time().is_some_and(|t| t <= target)
+}
+
+/// Longest run of consecutive solves that beat `target`.
+pub fn best_streak(solves: &[SolveResult], target: Duration) -> usize {
+    let mut best = 0;
+    let mut current = 0;
+    for solve in counted(solves) {
+        current = match beats(solve, target) {
+            true => current + 1,
+            false => 0,
+        };
+        best = best.max(current);
+    }
+    best
+}
+
+/// Number of trailing consecutive solves that beat `target`.
+pub fn streak(solves: &[SolveResult], target: Duration) -> usize {
+    counted(solves)
+        .rev()
+        .take_while(|s| beats(s, target))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Penalty;
+
+    fn solves(ms: &[u64]) -> Vec<SolveResult> {
+        ms.iter()
+            .map(|&ms| SolveResult::new(Duration::from_millis(ms)))
+            .collect()
+    }
+
+    #[test]
+    fn histogram_buckets() {
+        let secs = Duration::from_secs;
+        let ms = Duration::from_millis;
+        let mut solves = solves(&[10_200, 10_900, 12_500, 99_000]);
+        solves[3].penalty = Penalty::Dnf;
+        assert_eq!(
+            histogram(&solves, secs(1)),
+            [
+                (secs(10), 2),
+                (secs(11), 0),
+                (secs(12), 1),
+                (Duration::MAX, 1)
+            ]
+        );
+        assert_eq!(
+            histogram(&solves[..2], ms(500)),
+            [(secs(10), 1), (ms(10_500), 1)]
+        );
+        assert!(histogram(&[], secs(1)).is_empty());
+
+        // far too many buckets to fill in, only the ones with solves are kept
+        let spread = self::solves(&[1_000, 5_000_000]);
+        let degenerate = [(ms(1_000), 1), (secs(5_000), 1)];
+        assert_eq!(histogram(&spread, Duration::from_nanos(1)), degenerate);
+        assert_eq!(histogram(&spread, Duration::ZERO), degenerate);
+    }
+
+    #[test]
+    fn improvement_of_halves() {
+        assert_eq!(improvement(&solves(&[12_000, 11_000, 10_000])), None);
+
+        let secs = Duration::from_secs;
+        let mut solves = solves(&[12_000, 14_000, 99_000, 10_000, 9_000, 11_000]);
+        solves[2].penalty = Penalty::Dnf;
+        assert_eq!(improvement(&solves), Some((secs(13), secs(10))));
+    }
+
+    #[test]
+    fn best_average_and_target() {
+        let secs = Duration::from_secs;
+        let solves = solves(&[12_000, 10_000, 11_000, 13_000, 11_000, 14_000, 9_000]);
+        // ao5s of 11.333, 11.667 and 12
+        assert_eq!(best_average(&solves, 5), Some(secs(34) / 3));
+        assert_eq!(best_average(&solves[..4], 5), None);
+
+        // the last 4 are 13, 11, 14 and 9
+        assert_eq!(ao5_target(&solves, secs(12)), Some(secs(12)));
+        let ms = Duration::from_millis;
+        assert_eq!(ao5_target(&solves, ms(11_500)), Some(ms(10_500)));
+        // even a solve faster than 9 gives 11
+        assert_eq!(ao5_target(&solves, secs(11)), None);
+        assert_eq!(ao5_target(&solves[..3], secs(12)), None);
+    }
+
+    #[test]
+    fn ties_drop_only_one_solve() {
+        let secs = Duration::from_secs;
+        let tied = solves(&[10_000, 10_000, 12_000, 14_000, 14_000]);
+        assert_eq!(average(&tied, 5), Some(Some(secs(12))));
+        let same = solves(&[11_000; 5]);
+        assert_eq!(average(&same, 5), Some(Some(secs(11))));
+
+        // two DNFs tie for the worst, only one of them can be dropped
+        let mut dnfs = solves(&[10_000, 11_000, 12_000, 13_000, 14_000]);
+        dnfs[1].penalty = Penalty::Dnf;
+        assert_eq!(average(&dnfs, 5), Some(Some(secs(13))));
+        dnfs[3].penalty = Penalty::Dnf;
+        assert_eq!(average(&dnfs, 5), Some(None));
+    }
+
+    #[test]
+    fn mean_without_the_worst_single() {
+        assert_eq!(mean_without_worst(&solves(&[10_000])), None);
+        let secs = Duration::from_secs;
+        let mut solves = solves(&[10_000, 99_000, 30_000, 14_000]);
+        solves[1].penalty = Penalty::Dnf;
+        assert_eq!(mean_without_worst(&solves), Some(secs(12)));
+        assert_eq!(mean(&solves), Some(secs(18)));
+    }
+
+    #[test]
+    fn ewma_follows_recent_solves() {
+        assert_eq!(ewma(&[], 0.5), None);
+        let secs = Duration::from_secs;
+        let mut solves = solves(&[10_000, 99_000, 20_000, 30_000]);
+        solves[1].penalty = Penalty::Dnf;
+        assert_eq!(ewma(&solves, 0.5), Some(secs(2250) / 100));
+        assert_eq!(ewma(&solves, 1.0), Some(secs(30)));
+    }
+
+    #[test]
+    fn intervals_and_gaps() {
+        let secs = Duration::from_secs;
+        let mut solves = solves(&[10_000; 6]);
+        let start = solves[0].timestamp;
+        for (solve, at) in solves.iter_mut().zip([0, 30, 70, 100, 2000, 2040]) {
+            solve.timestamp = start + secs(at);
+        }
+        let intervals = solve_intervals(&solves);
+        assert_eq!(intervals, [30, 40, 30, 1900, 40].map(secs));
+        assert_eq!(gaps(&intervals, secs(600)), Some((secs(35), secs(40), 1)));
+        assert_eq!(gaps(&intervals, secs(10)), None);
+
+        // a clock that went backwards
+        solves[2].timestamp = start;
+        assert_eq!(solve_intervals(&solves).len(), 4);
+    }
+
+    #[test]
+    fn mo3_of_the_last_three() {
+        assert_eq!(mo3(&solves(&[10_000, 12_000])), None);
+        let secs = Duration::from_secs;
+        assert_eq!(mo3(&solves(&[9_000, 12_000, 15_000])), Some(Some(secs(12))));
+        assert_eq!(
+            mo3(&solves(&[1_000, 9_000, 12_000, 15_000])),
+            Some(Some(secs(12)))
+        );
+    }
+
+    #[test]
+    fn mo3_dnf_is_not_dropped() {
+        let mut solves = solves(&[9_000, 12_000, 15_000, 10_000]);
+        solves[1].penalty = Penalty::Dnf;
+        assert_eq!(mo3(&solves), Some(None));
+        // unlike ao5, a single DNF can't be dropped as the worst solve
+        assert!(average(&solves[..3], 3).is_some_and(|a| a.is_some()));
+
+        // once the DNF is more than 3 solves ago it doesn't count anymore, +2 does
+        solves.push(SolveResult::new(Duration::from_secs(6)));
+        solves[2].penalty = Penalty::Plus2;
+        assert_eq!(mo3(&solves), Some(Some(Duration::from_secs(11))));
+
+        // warmup solves are skipped
+        solves[4].warmup = true;
+        assert_eq!(mo3(&solves), Some(None));
+    }
+}
@@ -0,0 +1,140 @@
+use std::error::Error;
+#[cfg(feature = "sqlite")]
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::history::{self, SolveResult};
+
+/// Where the history is kept between sessions.
+pub trait HistoryStore {
+    fn load(&self) -> Result<Vec<SolveResult>, Box<dyn Error>>;
+
+    /// Replaces the stored history with `solves`.
+    fn save(&self, solves: &[SolveResult]) -> Result<(), Box<dyn Error>>;
+}
+
+/// The store selected by the config, `--db` or the JSON file in the data directory.
+pub fn open(config: &Config) -> Box<dyn HistoryStore> {
+    match &config.db {
+        #[cfg(feature = "sqlite")]
+        Some(path) => Box::new(SqliteStore::new(path)),
+        _ => Box::new(JsonStore),
+    }
+}
+
+/// The history as a JSON file in the data directory.
+pub struct JsonStore;
+
+impl HistoryStore for JsonStore {
+    fn load(&self) -> Result<Vec<SolveResult>, Box<dyn Error>> {
+        history::load()
+    }
+
+    fn save(&self, solves: &[SolveResult]) -> Result<(), Box<dyn Error>> {
+        history::save(solves)
+    }
+}
+
+/// The history in a SQLite database, one row per solve. The times, penalty and session have
+/// their own columns for querying, the whole solve is kept as JSON so no field gets lost.
+#[cfg(feature = "sqlite")]
+pub struct SqliteStore {
+    path: PathBuf,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStore {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    fn connect(&self) -> Result<rusqlite::Connection, Box<dyn Error>> {
+        let conn = rusqlite::Connection::open(&self.path)
+            .map_err(|e| format!("error opening {}: {e}", self.path.display()))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS solves (
+                timestamp_ms INTEGER NOT NULL,
+                time_ms INTEGER NOT NULL,
+                penalty TEXT NOT NULL,
+                session TEXT,
+                solve TEXT NOT NULL
+            )",
+        )?;
+        Ok(conn)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl HistoryStore for SqliteStore {
+    fn load(&self) -> Result<Vec<SolveResult>, Box<dyn Error>> {
+        let conn = self.connect()?;
+        let mut select = conn.prepare("SELECT solve FROM solves ORDER BY rowid")?;
+        let rows = select.query_map([], |row| row.get::<_, String>(0))?;
+        let mut solves = Vec::new();
+        for json in rows {
+            let solve = serde_json::from_str(&json?)
+                .map_err(|e| format!("error parsing {}: {e}", self.path.display()))?;
+            solves.push(solve);
+        }
+        Ok(solves)
+    }
+
+    fn save(&self, solves: &[SolveResult]) -> Result<(), Box<dyn Error>> {
+        let mut conn = self.connect()?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM solves", [])?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO solves (timestamp_ms, time_ms, penalty, session, solve)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for solve in solves {
+                let timestamp = solve
+                    .timestamp
+                    .duration_since(std::time::SystemTime::UNIX_EPOCH)
+                    .map_or(0, |t| t.as_millis() as i64);
+                insert.execute(rusqlite::params![
+                    timestamp,
+                    solve.time.as_millis() as i64,
+                    format!("{:?}", solve.penalty),
+                    solve.session,
+                    serde_json::to_string(solve)?,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::history::Penalty;
+
+    #[test]
+    fn sqlite_round_trip() {
+        let dir = std::env::temp_dir().join(format!("cube-tuimer-sqlite-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = SqliteStore::new(&dir.join("history.db"));
+        assert_eq!(store.load().unwrap(), []);
+
+        let mut solves = vec![
+            SolveResult::new(Duration::from_millis(12_345)),
+            SolveResult::new(Duration::from_millis(9_870)),
+        ];
+        solves[1].penalty = Penalty::Plus2;
+        solves[1].session = Some("oh".into());
+        store.save(&solves).unwrap();
+        assert_eq!(store.load().unwrap(), solves);
+
+        // saving replaces the rows instead of adding to them
+        store.save(&solves[..1]).unwrap();
+        assert_eq!(store.load().unwrap(), solves[..1]);
+    }
+}
@@ -0,0 +1,43 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A multi-line text field, the cursor is always at the end.
+#[derive(Clone, Debug, Default)]
+pub struct TextInput {
+    pub text: String,
+    max_len: usize,
+}
+
+pub enum Action {
+    None,
+    Submit,
+    Cancel,
+}
+
+impl TextInput {
+    pub fn new(text: String, max_len: usize) -> Self {
+        Self { text, max_len }
+    }
+
+    /// `enter` inserts a newline, `ctrl-s` submits and `esc` cancels.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Action::Submit;
+            }
+            KeyCode::Esc => return Action::Cancel,
+            KeyCode::Enter => self.push('\n'),
+            KeyCode::Char(c) => self.push(c),
+            KeyCode::Backspace => {
+                self.text.pop();
+            }
+            _ => (),
+        }
+        Action::None
+    }
+
+    fn push(&mut self, c: char) {
+        if self.text.chars().count() < self.max_len {
+            self.text.push(c);
+        }
+    }
+}